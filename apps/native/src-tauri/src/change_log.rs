@@ -0,0 +1,105 @@
+/**
+ * Copyright 2026 Google LLC
+ * Licensed under the Apache License, Version 2.0
+ */
+
+//! Append-only log of local mutations to syncable tables.
+//!
+//! `collect_local_changes` used to scan each table directly for rows with
+//! `synced_at IS NULL`, which loses or double-counts a row that's edited again
+//! while a sync is in flight. Every mutation to a syncable table is instead
+//! appended here; [`coalesced_unsynced`] folds that log back down to one entry per
+//! `(table_name, row_id)` - latest op wins, a delete after an upsert collapses to
+//! just the delete - before a sync round builds `SyncRecord`s from it. Progress is
+//! then tracked by stamping `synced_seq` on the rows a push actually covered,
+//! rather than by mutating a flag on the source-of-truth row.
+
+use crate::db::Database;
+use rusqlite::params;
+
+#[derive(Debug, Clone)]
+pub struct ChangeLogEntry {
+    pub seq: i64,
+    pub row_id: String,
+    pub op: String,
+    pub payload_json: Option<String>,
+}
+
+/// Append a mutation. `op` is `"upsert"` or `"delete"`; `payload_json` carries the
+/// row's syncable fields for an upsert and is `None` for a delete.
+pub fn append(
+    db: &Database,
+    table_name: &str,
+    row_id: &str,
+    op: &str,
+    payload_json: Option<String>,
+) -> Result<i64, String> {
+    db.conn
+        .execute(
+            "INSERT INTO change_log (table_name, row_id, op, payload_json, created_at, synced_seq)
+             VALUES (?1, ?2, ?3, ?4, ?5, NULL)",
+            params![table_name, row_id, op, payload_json, chrono::Utc::now().timestamp()],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(db.conn.last_insert_rowid())
+}
+
+/// Coalesced unsynced entries for one table: the latest mutation per `row_id`.
+pub fn coalesced_unsynced(db: &Database, table_name: &str) -> Result<Vec<ChangeLogEntry>, String> {
+    let mut stmt = db
+        .conn
+        .prepare(
+            "SELECT seq, row_id, op, payload_json
+             FROM change_log AS c
+             WHERE table_name = ?1 AND synced_seq IS NULL
+               AND seq = (
+                   SELECT MAX(seq) FROM change_log AS latest
+                   WHERE latest.table_name = c.table_name
+                     AND latest.row_id = c.row_id
+                     AND latest.synced_seq IS NULL
+               )
+             ORDER BY seq",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![table_name], |row| {
+            Ok(ChangeLogEntry {
+                seq: row.get(0)?,
+                row_id: row.get(1)?,
+                op: row.get(2)?,
+                payload_json: row.get(3)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Stamp every unsynced entry for `(table_name, row_id)` up to and including
+/// `through_seq` as synced. A row mutated again after being collected but before
+/// the push was confirmed keeps its newer entry pending for the next round.
+pub fn mark_synced(db: &Database, table_name: &str, row_id: &str, through_seq: i64) -> Result<(), String> {
+    db.conn
+        .execute(
+            "UPDATE change_log SET synced_seq = ?1
+             WHERE table_name = ?2 AND row_id = ?3 AND synced_seq IS NULL AND seq <= ?1",
+            params![through_seq, table_name, row_id],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Count unsynced rows across all tables, coalesced per `(table_name, row_id)` so a
+/// row edited twice before syncing counts once.
+pub fn count_pending(db: &Database) -> Result<usize, String> {
+    let count: i64 = db
+        .conn
+        .query_row(
+            "SELECT COUNT(DISTINCT table_name || ':' || row_id) FROM change_log WHERE synced_seq IS NULL",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(count as usize)
+}
@@ -49,6 +49,48 @@ impl Database {
                 last_sync_timestamp INTEGER NOT NULL DEFAULT 0,
                 cursor TEXT
             );
+
+            -- Append-only audit log of rows resolved by last-write-wins conflict
+            -- resolution, so the UI can surface e.g. \"this session was overwritten\".
+            CREATE TABLE IF NOT EXISTS sync_conflicts (
+                id TEXT NOT NULL,
+                table_name TEXT NOT NULL,
+                local_json TEXT NOT NULL,
+                remote_json TEXT NOT NULL,
+                resolved_side TEXT NOT NULL,
+                resolved_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_sync_conflicts_id
+                ON sync_conflicts(id);
+
+            -- Append-only record of every local mutation to a syncable table.
+            -- `coalesced_unsynced` reads entries from here (latest op per row_id
+            -- wins) instead of scanning the source table, so sync stays
+            -- deterministic and idempotent across partial/interrupted runs.
+            CREATE TABLE IF NOT EXISTS change_log (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                table_name TEXT NOT NULL,
+                row_id TEXT NOT NULL,
+                op TEXT NOT NULL,
+                payload_json TEXT,
+                created_at INTEGER NOT NULL,
+                synced_seq INTEGER
+            );
+            CREATE INDEX IF NOT EXISTS idx_change_log_row
+                ON change_log(table_name, row_id);
+            CREATE INDEX IF NOT EXISTS idx_change_log_synced_seq
+                ON change_log(synced_seq);
+
+            -- Full-text index over session_history's searchable content. Kept in
+            -- sync by explicit re-index calls from save_session/delete_session
+            -- rather than SQL triggers, matching how change_log is maintained
+            -- explicitly in application code instead of implicitly in the schema.
+            -- Requires rusqlite's FTS5-enabled bundled SQLite.
+            CREATE VIRTUAL TABLE IF NOT EXISTS session_history_fts USING fts5(
+                id UNINDEXED,
+                content,
+                tokenize = 'porter'
+            );
             "
         )?;
         Ok(())
@@ -1,11 +1,17 @@
+mod change_log;
 mod db;
 mod session_history;
+mod sync;
+mod sync_crypto;
+mod sync_scheduler;
+mod synced_table;
 
 use std::sync::Mutex;
 use tauri::Manager;
 
 pub struct AppState {
     pub db: Mutex<db::Database>,
+    pub sync_scheduler: sync_scheduler::SyncScheduler,
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -24,6 +30,7 @@ pub fn run() {
 
             app.manage(AppState {
                 db: Mutex::new(database),
+                sync_scheduler: sync_scheduler::SyncScheduler::new(),
             });
 
             Ok(())
@@ -36,6 +43,23 @@ pub fn run() {
             session_history::delete_session,
             session_history::clear_sessions,
             session_history::count_sessions,
+            session_history::session_stats,
+            session_history::language_breakdown,
+            session_history::language_pair_breakdown,
+            session_history::activity_calendar,
+            session_history::search_sessions,
+            session_history::export_sessions,
+            session_history::import_sessions,
+            // Sync commands
+            sync::SyncService::sync_now,
+            sync::SyncService::sync_push,
+            sync::SyncService::sync_pull,
+            sync::SyncService::get_sync_status,
+            sync::SyncService::configure_sync,
+            sync::SyncService::trigger_sync_check,
+            sync::SyncService::reset_sync,
+            sync::SyncService::export_encryption_key,
+            sync::SyncService::import_encryption_key,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use tauri::State;
-use crate::AppState;
+use crate::{change_log, AppState};
 use rusqlite::params;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,12 +27,127 @@ pub struct SessionHistoryFilter {
     #[serde(rename = "fromLanguage")]
     pub from_language: Option<String>,
     pub mode: Option<String>,
+    /// Multi-select form of `language`; takes precedence over it when present.
+    pub languages: Option<Vec<String>>,
+    /// Multi-select form of `mode`; takes precedence over it when present.
+    pub modes: Option<Vec<String>>,
+    pub voice: Option<String>,
     #[serde(rename = "fromDate")]
     pub from_date: Option<i64>,
     #[serde(rename = "toDate")]
     pub to_date: Option<i64>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// Column to sort by; anything outside [`ORDER_COLUMNS`] falls back to `completed_at`.
+    #[serde(rename = "orderBy")]
+    pub order_by: Option<String>,
+    /// `"asc"` or `"desc"` (case-insensitive); anything else falls back to `desc`.
+    #[serde(rename = "orderDir")]
+    pub order_dir: Option<String>,
+}
+
+/// Sort columns `get_all_sessions`/`count_sessions` will accept via
+/// `SessionHistoryFilter::order_by`, so a caller can't smuggle arbitrary SQL
+/// into an `ORDER BY` clause.
+const ORDER_COLUMNS: &[&str] = &["completed_at", "language", "mode", "from_language", "voice"];
+
+/// Accumulates `WHERE` conditions and their bound parameters in lockstep, so
+/// callers compose parameterized SQL without hand-interpolating values (or
+/// `LIMIT`/`OFFSET` integers) into the query string.
+struct QueryBuilder {
+    conditions: Vec<String>,
+    params: Vec<Box<dyn rusqlite::ToSql>>,
+}
+
+impl QueryBuilder {
+    fn new() -> Self {
+        Self { conditions: Vec::new(), params: Vec::new() }
+    }
+
+    /// Adds a single `clause = ?` condition bound to `value`.
+    fn push<T: rusqlite::ToSql + 'static>(&mut self, clause: &str, value: T) {
+        self.conditions.push(clause.to_string());
+        self.params.push(Box::new(value));
+    }
+
+    /// Adds a `column IN (?, ?, ...)` condition, one bound parameter per
+    /// value. A no-op for an empty list, rather than emitting `IN ()`.
+    fn push_in<T: rusqlite::ToSql + Clone + 'static>(&mut self, column: &str, values: &[T]) {
+        if values.is_empty() {
+            return;
+        }
+        let placeholders = values.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+        self.conditions.push(format!("{} IN ({})", column, placeholders));
+        for value in values {
+            self.params.push(Box::new(value.clone()));
+        }
+    }
+
+    /// An ` AND ...` fragment combining every condition added so far, or the
+    /// empty string if none were added.
+    fn where_clause(&self) -> String {
+        if self.conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" AND {}", self.conditions.join(" AND "))
+        }
+    }
+
+    fn params_refs(&self) -> Vec<&dyn rusqlite::ToSql> {
+        self.params.iter().map(|p| p.as_ref()).collect()
+    }
+}
+
+/// Compiles a [`SessionHistoryFilter`]'s structured fields into a
+/// [`QueryBuilder`], shared by every command that queries `session_history`
+/// against it.
+fn filter_conditions(filter: &SessionHistoryFilter) -> QueryBuilder {
+    let mut qb = QueryBuilder::new();
+
+    if let Some(ref languages) = filter.languages {
+        qb.push_in("language", languages);
+    } else if let Some(ref language) = filter.language {
+        qb.push("language = ?", language.clone());
+    }
+
+    if let Some(ref from_language) = filter.from_language {
+        qb.push("from_language = ?", from_language.clone());
+    }
+
+    if let Some(ref modes) = filter.modes {
+        qb.push_in("mode", modes);
+    } else if let Some(ref mode) = filter.mode {
+        qb.push("mode = ?", mode.clone());
+    }
+
+    if let Some(ref voice) = filter.voice {
+        qb.push("voice = ?", voice.clone());
+    }
+
+    if let Some(from_date) = filter.from_date {
+        qb.push("completed_at >= ?", from_date);
+    }
+    if let Some(to_date) = filter.to_date {
+        qb.push("completed_at <= ?", to_date);
+    }
+
+    qb
+}
+
+/// An `ORDER BY <column> <ASC|DESC>` clause honoring `order_by`/`order_dir`,
+/// defaulting to `completed_at DESC` and rejecting anything outside
+/// [`ORDER_COLUMNS`].
+fn order_clause(filter: &SessionHistoryFilter) -> String {
+    let column = filter
+        .order_by
+        .as_deref()
+        .filter(|c| ORDER_COLUMNS.contains(c))
+        .unwrap_or("completed_at");
+    let dir = match filter.order_dir.as_deref() {
+        Some(d) if d.eq_ignore_ascii_case("asc") => "ASC",
+        _ => "DESC",
+    };
+    format!(" ORDER BY {} {}", column, dir)
 }
 
 #[tauri::command]
@@ -66,6 +181,55 @@ pub async fn save_session(
         ],
     ).map_err(|e| e.to_string())?;
 
+    // Record this mutation in the append-only change log so sync picks it up,
+    // rather than scanning session_history for a stale `synced_at` flag.
+    let payload = serde_json::json!({
+        "mission_json": mission_json,
+        "language": entry.language,
+        "from_language": entry.from_language,
+        "mode": entry.mode,
+        "voice": entry.voice,
+        "result_json": result_json,
+        "completed_at": entry.completed_at,
+    })
+    .to_string();
+    change_log::append(&db, "session_history", &entry.id, "upsert", Some(payload))?;
+
+    reindex_fts(&db, &entry.id, mission_json.as_deref(), &result_json)?;
+
+    Ok(())
+}
+
+/// Re-index one session's searchable content into `session_history_fts`, dropping
+/// any stale row first since FTS5 doesn't support an in-place `UPDATE` keyed by an
+/// arbitrary text id. `mission_json`/`result_json` are opaque blobs to this layer,
+/// so the whole JSON text is indexed rather than any particular field inside it.
+fn reindex_fts(
+    db: &std::sync::MutexGuard<crate::db::Database>,
+    id: &str,
+    mission_json: Option<&str>,
+    result_json: &str,
+) -> Result<(), String> {
+    db.conn
+        .execute("DELETE FROM session_history_fts WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
+
+    let content = format!("{} {}", mission_json.unwrap_or_default(), result_json);
+    db.conn
+        .execute(
+            "INSERT INTO session_history_fts (id, content) VALUES (?1, ?2)",
+            params![id, content],
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+/// Drop a session's entry from the full-text index, e.g. once it's (soft-)deleted.
+fn remove_from_fts(db: &std::sync::MutexGuard<crate::db::Database>, id: &str) -> Result<(), String> {
+    db.conn
+        .execute("DELETE FROM session_history_fts WHERE id = ?1", [id])
+        .map_err(|e| e.to_string())?;
     Ok(())
 }
 
@@ -77,72 +241,27 @@ pub async fn get_all_sessions(
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let filter = filter.unwrap_or_default();
 
+    let mut qb = filter_conditions(&filter);
+
     let mut sql = String::from(
         "SELECT id, mission_json, language, from_language, mode, voice, result_json,
                 completed_at, sync_version, synced_at, deleted, deleted_at
-         FROM session_history WHERE deleted = 0"
+         FROM session_history WHERE deleted = 0",
     );
-
-    let mut conditions = Vec::new();
-
-    if filter.language.is_some() {
-        conditions.push("language = ?");
-    }
-    if filter.from_language.is_some() {
-        conditions.push("from_language = ?");
-    }
-    if filter.mode.is_some() {
-        conditions.push("mode = ?");
-    }
-    if filter.from_date.is_some() {
-        conditions.push("completed_at >= ?");
-    }
-    if filter.to_date.is_some() {
-        conditions.push("completed_at <= ?");
-    }
-
-    for cond in &conditions {
-        sql.push_str(" AND ");
-        sql.push_str(cond);
-    }
-
-    sql.push_str(" ORDER BY completed_at DESC");
+    sql.push_str(&qb.where_clause());
+    sql.push_str(&order_clause(&filter));
 
     if let Some(limit) = filter.limit {
-        sql.push_str(&format!(" LIMIT {}", limit));
+        sql.push_str(" LIMIT ?");
+        qb.params.push(Box::new(limit));
     }
     if let Some(offset) = filter.offset {
-        sql.push_str(&format!(" OFFSET {}", offset));
+        sql.push_str(" OFFSET ?");
+        qb.params.push(Box::new(offset));
     }
 
     let mut stmt = db.conn.prepare(&sql).map_err(|e| e.to_string())?;
-
-    // Build params dynamically
-    let mut param_idx = 1usize;
-    let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-
-    if let Some(ref lang) = filter.language {
-        params.push(Box::new(lang.clone()));
-        param_idx += 1;
-    }
-    if let Some(ref from_lang) = filter.from_language {
-        params.push(Box::new(from_lang.clone()));
-        param_idx += 1;
-    }
-    if let Some(ref mode) = filter.mode {
-        params.push(Box::new(mode.clone()));
-        param_idx += 1;
-    }
-    if let Some(from_date) = filter.from_date {
-        params.push(Box::new(from_date));
-        param_idx += 1;
-    }
-    if let Some(to_date) = filter.to_date {
-        params.push(Box::new(to_date));
-        let _ = param_idx; // Suppress unused warning
-    }
-
-    let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+    let params_refs = qb.params_refs();
 
     let rows = stmt.query_map(params_refs.as_slice(), |row| {
         let mission_json: Option<String> = row.get(1)?;
@@ -229,6 +348,9 @@ pub async fn delete_session(
         params![now, id],
     ).map_err(|e| e.to_string())?;
 
+    change_log::append(&db, "session_history", &id, "delete", None)?;
+    remove_from_fts(&db, &id)?;
+
     Ok(())
 }
 
@@ -237,6 +359,17 @@ pub async fn clear_sessions(state: State<'_, AppState>) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let now = chrono::Utc::now().timestamp_millis();
 
+    let mut stmt = db
+        .conn
+        .prepare("SELECT id FROM session_history WHERE deleted = 0")
+        .map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    drop(stmt);
+
     db.conn.execute(
         "UPDATE session_history
          SET deleted = 1, deleted_at = ?1, synced_at = NULL
@@ -244,6 +377,11 @@ pub async fn clear_sessions(state: State<'_, AppState>) -> Result<(), String> {
         [now],
     ).map_err(|e| e.to_string())?;
 
+    for id in ids {
+        change_log::append(&db, "session_history", &id, "delete", None)?;
+        remove_from_fts(&db, &id)?;
+    }
+
     Ok(())
 }
 
@@ -255,35 +393,597 @@ pub async fn count_sessions(
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let filter = filter.unwrap_or_default();
 
+    let qb = filter_conditions(&filter);
+
     let mut sql = String::from("SELECT COUNT(*) FROM session_history WHERE deleted = 0");
+    sql.push_str(&qb.where_clause());
 
-    if filter.language.is_some() {
-        sql.push_str(" AND language = ?");
-    }
-    if filter.from_language.is_some() {
-        sql.push_str(" AND from_language = ?");
+    let mut stmt = db.conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let params_refs = qb.params_refs();
+
+    let count: i64 = stmt.query_row(params_refs.as_slice(), |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+
+    Ok(count)
+}
+
+// ===== Analytics =====
+//
+// These answer "how am I doing" questions directly instead of making the
+// frontend page through `get_all_sessions` and tally things client-side.
+// `result_json` is an opaque blob we don't otherwise interpret, so
+// `extract_duration_seconds` reads its `durationSeconds` field defensively -
+// same as Gemini's setup JSON is picked apart on the server - rather than
+// assuming every caller's result payload carries it.
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionStats {
+    pub total_sessions: i64,
+    #[serde(rename = "totalPracticedSeconds")]
+    pub total_practiced_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageBreakdownEntry {
+    pub language: String,
+    pub mode: String,
+    #[serde(rename = "sessionCount")]
+    pub session_count: i64,
+    #[serde(rename = "practicedSeconds")]
+    pub practiced_seconds: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityDay {
+    pub date: String,
+    #[serde(rename = "sessionCount")]
+    pub session_count: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityCalendar {
+    pub days: Vec<ActivityDay>,
+    #[serde(rename = "currentStreak")]
+    pub current_streak: i64,
+    #[serde(rename = "longestStreak")]
+    pub longest_streak: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguagePairCount {
+    #[serde(rename = "fromLanguage")]
+    pub from_language: String,
+    pub language: String,
+    #[serde(rename = "sessionCount")]
+    pub session_count: i64,
+}
+
+fn extract_duration_seconds(result_json: &str) -> i64 {
+    serde_json::from_str::<serde_json::Value>(result_json)
+        .ok()
+        .and_then(|v| v.get("durationSeconds").and_then(|d| d.as_i64()))
+        .unwrap_or(0)
+}
+
+/// One row's worth of the columns every analytics command groups/aggregates over,
+/// fetched with the same filter-to-SQL approach as [`get_all_sessions`].
+struct AnalyticsRow {
+    language: String,
+    from_language: String,
+    mode: String,
+    result_json: String,
+    completed_at: i64,
+}
+
+fn filtered_analytics_rows(
+    db: &std::sync::MutexGuard<crate::db::Database>,
+    filter: &SessionHistoryFilter,
+) -> Result<Vec<AnalyticsRow>, String> {
+    let qb = filter_conditions(filter);
+
+    let mut sql = String::from(
+        "SELECT language, from_language, mode, result_json, completed_at
+         FROM session_history WHERE deleted = 0",
+    );
+    sql.push_str(&qb.where_clause());
+
+    let mut stmt = db.conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let params_refs = qb.params_refs();
+
+    let rows = stmt
+        .query_map(params_refs.as_slice(), |row| {
+            Ok(AnalyticsRow {
+                language: row.get(0)?,
+                from_language: row.get(1)?,
+                mode: row.get(2)?,
+                result_json: row.get(3)?,
+                completed_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Total sessions and total practiced time over the (optionally filtered) history.
+#[tauri::command]
+pub async fn session_stats(
+    state: State<'_, AppState>,
+    filter: Option<SessionHistoryFilter>,
+) -> Result<SessionStats, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let rows = filtered_analytics_rows(&db, &filter.unwrap_or_default())?;
+
+    Ok(SessionStats {
+        total_sessions: rows.len() as i64,
+        total_practiced_seconds: rows.iter().map(|r| extract_duration_seconds(&r.result_json)).sum(),
+    })
+}
+
+/// Session count and practiced time grouped by `(language, mode)`.
+#[tauri::command]
+pub async fn language_breakdown(
+    state: State<'_, AppState>,
+    filter: Option<SessionHistoryFilter>,
+) -> Result<Vec<LanguageBreakdownEntry>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let rows = filtered_analytics_rows(&db, &filter.unwrap_or_default())?;
+
+    let mut grouped: std::collections::BTreeMap<(String, String), (i64, i64)> = std::collections::BTreeMap::new();
+    for row in &rows {
+        let entry = grouped.entry((row.language.clone(), row.mode.clone())).or_insert((0, 0));
+        entry.0 += 1;
+        entry.1 += extract_duration_seconds(&row.result_json);
     }
-    if filter.mode.is_some() {
-        sql.push_str(" AND mode = ?");
+
+    Ok(grouped
+        .into_iter()
+        .map(|((language, mode), (session_count, practiced_seconds))| LanguageBreakdownEntry {
+            language,
+            mode,
+            session_count,
+            practiced_seconds,
+        })
+        .collect())
+}
+
+/// Per-`from_language`-to-`language` pair session counts, for a "what am I
+/// learning from what" view.
+#[tauri::command]
+pub async fn language_pair_breakdown(
+    state: State<'_, AppState>,
+    filter: Option<SessionHistoryFilter>,
+) -> Result<Vec<LanguagePairCount>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let rows = filtered_analytics_rows(&db, &filter.unwrap_or_default())?;
+
+    let mut grouped: std::collections::BTreeMap<(String, String), i64> = std::collections::BTreeMap::new();
+    for row in &rows {
+        *grouped.entry((row.from_language.clone(), row.language.clone())).or_insert(0) += 1;
     }
 
-    let mut stmt = db.conn.prepare(&sql).map_err(|e| e.to_string())?;
+    Ok(grouped
+        .into_iter()
+        .map(|((from_language, language), session_count)| LanguagePairCount {
+            from_language,
+            language,
+            session_count,
+        })
+        .collect())
+}
+
+/// Day-by-day session counts bucketed by `completed_at`, plus the current and
+/// longest consecutive-day streaks, for a heatmap/streak view.
+#[tauri::command]
+pub async fn activity_calendar(
+    state: State<'_, AppState>,
+    filter: Option<SessionHistoryFilter>,
+) -> Result<ActivityCalendar, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let rows = filtered_analytics_rows(&db, &filter.unwrap_or_default())?;
 
-    let mut params: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
-    if let Some(ref lang) = filter.language {
-        params.push(Box::new(lang.clone()));
+    let mut counts: std::collections::BTreeMap<chrono::NaiveDate, i64> = std::collections::BTreeMap::new();
+    for row in &rows {
+        if let Some(dt) = chrono::DateTime::from_timestamp_millis(row.completed_at) {
+            *counts.entry(dt.date_naive()).or_insert(0) += 1;
+        }
     }
-    if let Some(ref from_lang) = filter.from_language {
-        params.push(Box::new(from_lang.clone()));
+
+    let days: Vec<ActivityDay> = counts
+        .iter()
+        .map(|(date, session_count)| ActivityDay {
+            date: date.format("%Y-%m-%d").to_string(),
+            session_count: *session_count,
+        })
+        .collect();
+
+    let mut longest_streak = 0i64;
+    let mut running_streak = 0i64;
+    let mut previous_day: Option<chrono::NaiveDate> = None;
+    for date in counts.keys() {
+        running_streak = match previous_day {
+            Some(prev) if *date == prev.succ_opt().unwrap_or(prev) => running_streak + 1,
+            _ => 1,
+        };
+        longest_streak = longest_streak.max(running_streak);
+        previous_day = Some(*date);
+    }
+
+    // Current streak only counts if the most recent active day is today or
+    // yesterday - otherwise the streak has already been broken by inactivity.
+    let current_streak = match (previous_day, counts.keys().next_back()) {
+        (Some(last_day), Some(_)) => {
+            let today = chrono::Utc::now().date_naive();
+            if last_day == today || last_day == today.pred_opt().unwrap_or(today) {
+                let mut streak = 1i64;
+                let mut day = last_day;
+                while let Some(prev) = day.pred_opt() {
+                    if counts.contains_key(&prev) {
+                        streak += 1;
+                        day = prev;
+                    } else {
+                        break;
+                    }
+                }
+                streak
+            } else {
+                0
+            }
+        }
+        _ => 0,
+    };
+
+    Ok(ActivityCalendar { days, current_streak, longest_streak })
+}
+
+/// A session matching a [`search_sessions`] query, with a highlighted snippet
+/// showing why it matched.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSearchResult {
+    #[serde(flatten)]
+    pub entry: SessionHistoryEntry,
+    pub snippet: String,
+}
+
+/// Full-text search over session content via the `session_history_fts` index,
+/// ranked by `bm25()` (most relevant first) and still honoring `deleted = 0` plus
+/// any structured [`SessionHistoryFilter`] constraints.
+#[tauri::command]
+pub async fn search_sessions(
+    state: State<'_, AppState>,
+    query: String,
+    filter: Option<SessionHistoryFilter>,
+) -> Result<Vec<SessionSearchResult>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let filter = filter.unwrap_or_default();
+
+    let mut qb = filter_conditions(&filter);
+
+    let mut sql = String::from(
+        "SELECT sh.id, sh.mission_json, sh.language, sh.from_language, sh.mode, sh.voice,
+                sh.result_json, sh.completed_at, sh.sync_version, sh.synced_at, sh.deleted,
+                sh.deleted_at, snippet(session_history_fts, 1, '<mark>', '</mark>', '...', 12)
+         FROM session_history_fts
+         JOIN session_history sh ON sh.id = session_history_fts.id
+         WHERE session_history_fts MATCH ?1 AND sh.deleted = 0",
+    );
+    sql.push_str(&qb.where_clause());
+    sql.push_str(" ORDER BY bm25(session_history_fts)");
+
+    if let Some(limit) = filter.limit {
+        sql.push_str(" LIMIT ?");
+        qb.params.push(Box::new(limit));
     }
-    if let Some(ref mode) = filter.mode {
-        params.push(Box::new(mode.clone()));
+    if let Some(offset) = filter.offset {
+        sql.push_str(" OFFSET ?");
+        qb.params.push(Box::new(offset));
     }
 
-    let params_refs: Vec<&dyn rusqlite::ToSql> = params.iter().map(|p| p.as_ref()).collect();
+    let mut stmt = db.conn.prepare(&sql).map_err(|e| e.to_string())?;
+
+    let mut params_refs: Vec<&dyn rusqlite::ToSql> = vec![&query];
+    params_refs.extend(qb.params_refs());
 
-    let count: i64 = stmt.query_row(params_refs.as_slice(), |row| row.get(0))
+    let rows = stmt
+        .query_map(params_refs.as_slice(), |row| {
+            let mission_json: Option<String> = row.get(1)?;
+            let result_json: String = row.get(6)?;
+            let deleted_int: i64 = row.get(10)?;
+
+            Ok(SessionSearchResult {
+                entry: SessionHistoryEntry {
+                    id: row.get(0)?,
+                    mission: mission_json.map(|s| serde_json::from_str(&s).unwrap_or(serde_json::Value::Null)),
+                    language: row.get(2)?,
+                    from_language: row.get(3)?,
+                    mode: row.get(4)?,
+                    voice: row.get(5)?,
+                    result: serde_json::from_str(&result_json).unwrap_or(serde_json::Value::Null),
+                    completed_at: row.get(7)?,
+                    sync_version: row.get(8)?,
+                    synced_at: row.get(9)?,
+                    deleted: Some(deleted_int != 0),
+                    deleted_at: row.get(11)?,
+                },
+                snippet: row.get(12)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+// ===== Backup export/import =====
+//
+// A portable escape hatch independent of `sync`/`qm_sync_client`: a single
+// JSON file a user can keep on their own disk and later feed into a fresh
+// install, with no server round-trip required.
+
+/// On-disk shape of a session history backup. `version` is bumped whenever
+/// the document layout changes incompatibly, so `import_sessions` can refuse
+/// an archive it doesn't know how to read instead of guessing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SessionHistoryArchive {
+    version: u8,
+    #[serde(rename = "exportedAt")]
+    exported_at: i64,
+    sessions: Vec<SessionHistoryEntry>,
+}
+
+const ARCHIVE_VERSION: u8 = 1;
+
+/// Stream every non-deleted (optionally filtered) session into a versioned
+/// JSON document at `path`, preserving `id`, `sync_version`, and timestamps
+/// so `import_sessions` can resolve collisions later. Returns the number of
+/// sessions written.
+#[tauri::command]
+pub async fn export_sessions(
+    state: State<'_, AppState>,
+    path: String,
+    filter: Option<SessionHistoryFilter>,
+) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let filter = filter.unwrap_or_default();
+
+    let qb = filter_conditions(&filter);
+
+    let mut sql = String::from(
+        "SELECT id, mission_json, language, from_language, mode, voice, result_json,
+                completed_at, sync_version, synced_at, deleted, deleted_at
+         FROM session_history WHERE deleted = 0",
+    );
+    sql.push_str(&qb.where_clause());
+    sql.push_str(" ORDER BY completed_at ASC");
+
+    let mut stmt = db.conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let params_refs = qb.params_refs();
+
+    let rows = stmt
+        .query_map(params_refs.as_slice(), |row| {
+            let mission_json: Option<String> = row.get(1)?;
+            let result_json: String = row.get(6)?;
+            let deleted_int: i64 = row.get(10)?;
+
+            Ok(SessionHistoryEntry {
+                id: row.get(0)?,
+                mission: mission_json.map(|s| serde_json::from_str(&s).unwrap_or(serde_json::Value::Null)),
+                language: row.get(2)?,
+                from_language: row.get(3)?,
+                mode: row.get(4)?,
+                voice: row.get(5)?,
+                result: serde_json::from_str(&result_json).unwrap_or(serde_json::Value::Null),
+                completed_at: row.get(7)?,
+                sync_version: row.get(8)?,
+                synced_at: row.get(9)?,
+                deleted: Some(deleted_int != 0),
+                deleted_at: row.get(11)?,
+            })
+        })
         .map_err(|e| e.to_string())?;
 
+    let sessions = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+    drop(stmt);
+    drop(db);
+
+    let count = sessions.len();
+    let archive = SessionHistoryArchive {
+        version: ARCHIVE_VERSION,
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        sessions,
+    };
+
+    let json = serde_json::to_string_pretty(&archive).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+
     Ok(count)
 }
+
+/// Read a [`SessionHistoryArchive`] from `path` and merge it in, resolving
+/// collisions on `id` by keeping whichever of (incoming, existing) carries
+/// the higher `sync_version`. Anything actually applied has `synced_at`
+/// cleared and is recorded in the change log, so a later sync re-propagates
+/// it rather than assuming the import already reached the server. Returns
+/// the number of sessions applied.
+#[tauri::command]
+pub async fn import_sessions(state: State<'_, AppState>, path: String) -> Result<usize, String> {
+    let json = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    let archive: SessionHistoryArchive = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+
+    if archive.version != ARCHIVE_VERSION {
+        return Err(format!(
+            "Unsupported session history archive version: {}",
+            archive.version
+        ));
+    }
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let mut imported = 0usize;
+
+    for entry in archive.sessions {
+        let existing_version: Option<i64> = db
+            .conn
+            .query_row(
+                "SELECT sync_version FROM session_history WHERE id = ?1",
+                [&entry.id],
+                |row| row.get(0),
+            )
+            .ok();
+
+        let incoming_version = entry.sync_version.unwrap_or(1);
+        if let Some(current_version) = existing_version {
+            if incoming_version <= current_version {
+                continue;
+            }
+        }
+
+        let mission_json = entry.mission.clone().map(|m| m.to_string());
+        let result_json = entry.result.to_string();
+        let deleted = entry.deleted.unwrap_or(false);
+
+        db.conn
+            .execute(
+                "INSERT OR REPLACE INTO session_history
+                 (id, mission_json, language, from_language, mode, voice, result_json, completed_at,
+                  sync_version, synced_at, deleted, deleted_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, NULL, ?10, ?11)",
+                params![
+                    entry.id,
+                    mission_json,
+                    entry.language,
+                    entry.from_language,
+                    entry.mode,
+                    entry.voice,
+                    result_json,
+                    entry.completed_at,
+                    incoming_version,
+                    if deleted { 1 } else { 0 },
+                    entry.deleted_at,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+
+        change_log::append(
+            &db,
+            "session_history",
+            &entry.id,
+            if deleted { "delete" } else { "upsert" },
+            None,
+        )?;
+
+        if deleted {
+            remove_from_fts(&db, &entry.id)?;
+        } else {
+            reindex_fts(&db, &entry.id, mission_json.as_deref(), &result_json)?;
+        }
+
+        imported += 1;
+    }
+
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::Connection;
+
+    fn seeded_conn() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE session_history (
+                id TEXT PRIMARY KEY,
+                language TEXT NOT NULL,
+                mode TEXT NOT NULL,
+                completed_at INTEGER NOT NULL,
+                deleted INTEGER NOT NULL DEFAULT 0
+             );
+             INSERT INTO session_history (id, language, mode, completed_at, deleted) VALUES
+                ('1', 'es', 'conversation', 100, 0),
+                ('2', 'fr', 'conversation', 200, 0),
+                ('3', 'es', 'drill', 300, 0),
+                ('4', 'es', 'drill', 400, 1);",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn run_filtered_ids(conn: &Connection, filter: &SessionHistoryFilter) -> Vec<String> {
+        let qb = filter_conditions(filter);
+        let mut sql = String::from("SELECT id FROM session_history WHERE deleted = 0");
+        sql.push_str(&qb.where_clause());
+        sql.push_str(&order_clause(filter));
+        let mut stmt = conn.prepare(&sql).unwrap();
+        let params_refs = qb.params_refs();
+        stmt.query_map(params_refs.as_slice(), |row| row.get::<_, String>(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn filter_conditions_empty_filter_matches_every_non_deleted_row() {
+        let conn = seeded_conn();
+        let ids = run_filtered_ids(&conn, &SessionHistoryFilter::default());
+        assert_eq!(ids, vec!["4".to_string(), "3".to_string(), "2".to_string(), "1".to_string()]);
+    }
+
+    #[test]
+    fn filter_conditions_single_language_filters_by_equality() {
+        let conn = seeded_conn();
+        let filter = SessionHistoryFilter { language: Some("es".to_string()), ..Default::default() };
+        let ids = run_filtered_ids(&conn, &filter);
+        assert_eq!(ids, vec!["3".to_string(), "1".to_string()]);
+    }
+
+    #[test]
+    fn filter_conditions_languages_list_takes_precedence_over_single_language() {
+        let conn = seeded_conn();
+        let filter = SessionHistoryFilter {
+            language: Some("fr".to_string()),
+            languages: Some(vec!["es".to_string()]),
+            ..Default::default()
+        };
+        let ids = run_filtered_ids(&conn, &filter);
+        assert_eq!(ids, vec!["3".to_string(), "1".to_string()]);
+    }
+
+    #[test]
+    fn filter_conditions_binds_values_rather_than_interpolating_them() {
+        let conn = seeded_conn();
+        // A value containing SQL syntax is still just a bound string, not SQL -
+        // this matches nothing, rather than executing as a statement separator.
+        let filter = SessionHistoryFilter {
+            language: Some("es'; DROP TABLE session_history; --".to_string()),
+            ..Default::default()
+        };
+        let ids = run_filtered_ids(&conn, &filter);
+        assert!(ids.is_empty());
+
+        // The table must still exist and be queryable afterwards.
+        let ids = run_filtered_ids(&conn, &SessionHistoryFilter::default());
+        assert_eq!(ids.len(), 4);
+    }
+
+    #[test]
+    fn push_in_with_empty_list_is_a_no_op() {
+        let mut qb = QueryBuilder::new();
+        qb.push_in::<String>("language", &[]);
+        assert!(qb.where_clause().is_empty());
+    }
+
+    #[test]
+    fn order_clause_rejects_unknown_columns_and_falls_back_to_completed_at_desc() {
+        let filter = SessionHistoryFilter { order_by: Some("id); DROP TABLE session_history; --".to_string()), ..Default::default() };
+        assert_eq!(order_clause(&filter), " ORDER BY completed_at DESC");
+    }
+
+    #[test]
+    fn order_clause_honors_a_known_column_and_ascending_direction() {
+        let filter = SessionHistoryFilter {
+            order_by: Some("language".to_string()),
+            order_dir: Some("asc".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(order_clause(&filter), " ORDER BY language ASC");
+    }
+}
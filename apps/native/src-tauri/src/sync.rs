@@ -3,12 +3,77 @@
  * Licensed under the Apache License, Version 2.0
  */
 
+use crate::change_log;
 use crate::db::Database;
-use qm_sync_client::{
-    Checkpoint, QmSyncClient, ReqwestHttpClient, SyncClientConfig, SyncRecord,
-};
+use crate::sync_crypto;
+use crate::synced_table::{self, SyncedTable};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use qm_sync_client::SyncRecord;
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
+use tauri::Emitter;
+
+/// Wire shape of one record in a `POST /api/history` request body, matching the
+/// server's `handlers::history::PushRecord`.
+#[derive(Serialize)]
+struct HistoryPushRecord {
+    table_name: String,
+    row_id: String,
+    data: serde_json::Value,
+    version: i64,
+    deleted: bool,
+}
+
+#[derive(Serialize)]
+struct HistoryPushRequestBody {
+    records: Vec<HistoryPushRecord>,
+}
+
+#[derive(Deserialize)]
+struct HistoryPushResponseBody {
+    synced_count: usize,
+}
+
+/// Wire shape of one record in a `GET /api/history` response, matching the
+/// server's `handlers::history::HistoryRecordOut`.
+#[derive(Deserialize)]
+struct HistoryPullRecord {
+    table_name: String,
+    row_id: String,
+    data: serde_json::Value,
+    version: i64,
+    deleted: bool,
+}
+
+#[derive(Deserialize)]
+struct HistoryPullResponseBody {
+    records: Vec<HistoryPullRecord>,
+    cursor: i64,
+}
+
+/// One coalesced change-log entry, already turned into the `SyncRecord` it becomes
+/// on the wire. Kept paired with its source `seq` so a successful push or a
+/// conflict resolution can stamp (or deliberately leave unstamped) the exact
+/// change-log row it came from.
+#[derive(Clone)]
+struct PendingChange {
+    record: SyncRecord,
+    seq: i64,
+}
+
+/// Default number of records pushed per delta round. Keeps request bodies bounded
+/// and a chunk's worth of progress durable before moving to the next one, so a
+/// backlog of thousands of sessions after weeks offline doesn't have to ride on one
+/// all-or-nothing request.
+pub(crate) const DEFAULT_BATCH_SIZE: usize = 200;
+
+/// Emitted on the `sync-progress` Tauri event after each batch, so the frontend can
+/// show a progress bar across a multi-round sync.
+#[derive(Debug, Clone, Serialize)]
+struct SyncProgress {
+    pushed: usize,
+    total: usize,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncResult {
@@ -20,6 +85,15 @@ pub struct SyncResult {
     pub synced_at: i64,
 }
 
+/// A portable copy of the locally stored Argon2id salt, for a second device to
+/// import so it derives the same encryption key from the shared passphrase.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionKeyBundle {
+    pub version: u8,
+    /// Base64-encoded salt.
+    pub salt: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncStatus {
     pub configured: bool,
@@ -29,111 +103,336 @@ pub struct SyncStatus {
     pub server_url: Option<String>,
 }
 
-#[derive(Debug, Clone)]
-pub struct SessionHistory {
-    pub id: String,
-    pub mission_json: Option<String>,
-    pub language: String,
-    pub from_language: String,
-    pub mode: String,
-    pub voice: String,
-    pub result_json: String,
-    pub completed_at: i64,
-    pub sync_version: i64,
-    pub synced_at: Option<i64>,
-    pub deleted: bool,
-    pub deleted_at: Option<i64>,
-}
-
 pub struct SyncService {
     db: Database,
+    tables: Vec<Box<dyn SyncedTable>>,
+    http: reqwest::Client,
 }
 
 impl SyncService {
     pub fn new(db: Database) -> Self {
-        Self { db }
+        Self {
+            db,
+            tables: synced_table::registry(),
+            http: reqwest::Client::new(),
+        }
     }
 
-    /// Main sync operation - pushes local changes and pulls remote changes
+    /// Find the registered `SyncedTable` for a record's `table_name`, if any.
+    fn table_for(&self, table_name: &str) -> Option<&dyn SyncedTable> {
+        self.tables
+            .iter()
+            .map(|t| t.as_ref())
+            .find(|t| t.table_name() == table_name)
+    }
+
+    /// Main sync operation - pushes local changes to, and pulls remote changes
+    /// from, the Axum server's `/api/history` endpoints.
     #[tauri::command]
     pub async fn sync_now(
+        app: tauri::AppHandle,
         server_url: String,
-        access_token: String,
-        refresh_token: String,
-        app_id: String,
-        api_key: String,
+        passphrase: String,
+        batch_size: Option<usize>,
     ) -> Result<SyncResult, String> {
         let db = Database::new(std::path::Path::new("immergo.db")).map_err(|e| e.to_string())?;
         let service = SyncService::new(db);
-        service.perform_sync(&server_url, &access_token, &refresh_token, &app_id, &api_key).await
+        service
+            .perform_sync(&app, &server_url, &passphrase, batch_size.unwrap_or(DEFAULT_BATCH_SIZE))
+            .await
     }
 
-    async fn perform_sync(
+    /// Push local changes in batches of `batch_size`, then pull everything new in
+    /// `batch_size`-sized pages, so neither direction rides on one all-or-nothing
+    /// request. Each batch/page's checkpoint and synced-state are persisted before
+    /// moving to the next, so a crash mid-sync picks back up rather than redoing
+    /// work.
+    pub(crate) async fn perform_sync(
         &self,
+        app: &tauri::AppHandle,
         server_url: &str,
-        access_token: &str,
-        refresh_token: &str,
-        app_id: &str,
-        api_key: &str,
+        passphrase: &str,
+        batch_size: usize,
     ) -> Result<SyncResult, String> {
         let synced_at = chrono::Utc::now().timestamp();
 
-        // Create sync client
-        let config = SyncClientConfig::new(server_url, app_id, api_key);
-        let http = ReqwestHttpClient::new();
-        let mut client = QmSyncClient::new(config, http);
-        client
-            .set_tokens(access_token, refresh_token, None)
+        // Derive the end-to-end encryption key, and the account id every
+        // `/api/history` call authenticates as, from the same locally stored salt.
+        // Only the salt is ever persisted; the passphrase and derived key live only
+        // for the duration of this sync.
+        let salt = self.get_or_create_encryption_salt()?;
+        let key = sync_crypto::derive_key(passphrase, &salt)?;
+        let account_id = sync_crypto::account_id(&key, &salt);
+
+        // Collect coalesced local changes from every registered table's change log
+        let local_changes = self.collect_local_changes(&key)?;
+        let total = local_changes.len();
+        println!("Collected {} local changes", total);
+
+        let mut total_pushed = 0;
+        for batch in local_changes.chunks(batch_size.max(1)) {
+            let pushed = self
+                .push_batch(server_url, &account_id, batch)
+                .await
+                .map_err(|e| format!("Sync failed: {}", e))?;
+            self.mark_records_synced(&batch[..pushed.min(batch.len())])?;
+            total_pushed += pushed;
+
+            let _ = app.emit("sync-progress", SyncProgress { pushed: total_pushed, total });
+        }
+
+        // Pull everything new, including the echo of what was just pushed. A row
+        // this round tried to push a now-stale version of - the server kept a
+        // newer one instead - comes back with a version that doesn't match what we
+        // sent, and is reconciled through `resolve_conflicts` rather than being
+        // blindly overwritten.
+        let (total_pulled, total_conflicts) = self
+            .pull_all(server_url, &account_id, &local_changes, batch_size, &key)
             .await
-            .map_err(|e| format!("Failed to set tokens: {}", e))?;
+            .map_err(|e| format!("Sync failed: {}", e))?;
 
-        // Collect local changes
-        let local_changes = self.collect_local_changes()?;
-        println!("Collected {} local changes", local_changes.len());
+        self.save_last_sync(synced_at)?;
 
-        // Get checkpoint
-        let checkpoint = self.get_checkpoint()?;
+        Ok(SyncResult {
+            pushed: total_pushed,
+            pulled: total_pulled,
+            conflicts: total_conflicts,
+            success: true,
+            error: None,
+            synced_at,
+        })
+    }
 
-        // Perform delta sync
-        let response = client
-            .delta(local_changes.clone(), checkpoint)
+    /// Push every locally unsynced change (including tombstones) without pulling
+    /// anything back. Useful for a foreground "save now" action, or as the write
+    /// half of a push/pull pair driven independently by the frontend instead of
+    /// the combined [`Self::sync_now`].
+    #[tauri::command]
+    pub async fn sync_push(
+        server_url: String,
+        passphrase: String,
+        batch_size: Option<usize>,
+    ) -> Result<SyncResult, String> {
+        let db = Database::new(std::path::Path::new("immergo.db")).map_err(|e| e.to_string())?;
+        let service = SyncService::new(db);
+        service
+            .perform_push(&server_url, &passphrase, batch_size.unwrap_or(DEFAULT_BATCH_SIZE))
             .await
-            .map_err(|e| format!("Sync failed: {}", e))?;
+    }
 
-        // Process push result
-        let pushed = response.push_response.synced_count;
-        let conflicts = response.push_response.conflicts.as_ref().map(|c| c.len()).unwrap_or(0);
+    /// Pull every remote change since the last saved checkpoint without pushing
+    /// anything local. The read half of a push/pull pair driven independently of
+    /// [`Self::sync_now`], e.g. on app launch before the user has made any edits.
+    #[tauri::command]
+    pub async fn sync_pull(
+        server_url: String,
+        passphrase: String,
+        batch_size: Option<usize>,
+    ) -> Result<SyncResult, String> {
+        let db = Database::new(std::path::Path::new("immergo.db")).map_err(|e| e.to_string())?;
+        let service = SyncService::new(db);
+        service
+            .perform_pull(&server_url, &passphrase, batch_size.unwrap_or(DEFAULT_BATCH_SIZE))
+            .await
+    }
 
-        // Mark pushed records as synced
-        if pushed > 0 {
-            let synced_records = &local_changes[..pushed.min(local_changes.len())];
-            self.mark_records_synced(synced_records, synced_at)?;
+    /// Push-only half of [`Self::perform_sync`]'s batch loop: nothing is pulled, so
+    /// a row whose push lost a version race isn't reconciled here - it's caught the
+    /// next time [`Self::perform_pull`] or [`Self::perform_sync`] runs.
+    async fn perform_push(
+        &self,
+        server_url: &str,
+        passphrase: &str,
+        batch_size: usize,
+    ) -> Result<SyncResult, String> {
+        let synced_at = chrono::Utc::now().timestamp();
+        let salt = self.get_or_create_encryption_salt()?;
+        let key = sync_crypto::derive_key(passphrase, &salt)?;
+        let account_id = sync_crypto::account_id(&key, &salt);
+
+        let local_changes = self.collect_local_changes(&key)?;
+
+        let mut total_pushed = 0;
+        for batch in local_changes.chunks(batch_size.max(1)) {
+            let pushed = self
+                .push_batch(server_url, &account_id, batch)
+                .await
+                .map_err(|e| format!("Push failed: {}", e))?;
+            self.mark_records_synced(&batch[..pushed.min(batch.len())])?;
+            total_pushed += pushed;
         }
 
-        // Process pull result
-        let pulled = response.pull_response.records.len();
-        if pulled > 0 {
-            self.apply_remote_changes(&response.pull_response.records)?;
-        }
+        Ok(SyncResult {
+            pushed: total_pushed,
+            pulled: 0,
+            conflicts: 0,
+            success: true,
+            error: None,
+            synced_at,
+        })
+    }
 
-        // Save new checkpoint
-        if let Some(new_checkpoint) = response.pull_response.checkpoint {
-            self.save_checkpoint(&new_checkpoint)?;
-        }
+    /// Pull-only half of [`Self::perform_sync`]'s loop: since nothing local is
+    /// pushed, there's nothing a pulled record could be conflicting with, so every
+    /// page is applied directly via [`Self::apply_remote_changes`].
+    async fn perform_pull(
+        &self,
+        server_url: &str,
+        passphrase: &str,
+        batch_size: usize,
+    ) -> Result<SyncResult, String> {
+        let synced_at = chrono::Utc::now().timestamp();
+        let salt = self.get_or_create_encryption_salt()?;
+        let key = sync_crypto::derive_key(passphrase, &salt)?;
+        let account_id = sync_crypto::account_id(&key, &salt);
+
+        let (total_pulled, _) = self
+            .pull_all(server_url, &account_id, &[], batch_size, &key)
+            .await
+            .map_err(|e| format!("Pull failed: {}", e))?;
 
-        // Update last sync timestamp
         self.save_last_sync(synced_at)?;
 
         Ok(SyncResult {
-            pushed,
-            pulled,
-            conflicts,
+            pushed: 0,
+            pulled: total_pulled,
+            conflicts: 0,
             success: true,
             error: None,
             synced_at,
         })
     }
 
+    /// Pull every page newer than the saved checkpoint, reconciling any record
+    /// whose row/table matches one of `pushed_changes` but whose version differs
+    /// (the server kept a different version than the one we just tried to push)
+    /// through [`Self::resolve_conflicts`], and applying everything else directly.
+    /// Returns `(total_pulled, total_conflicts)`.
+    async fn pull_all(
+        &self,
+        server_url: &str,
+        account_id: &str,
+        pushed_changes: &[PendingChange],
+        batch_size: usize,
+        key: &[u8; 32],
+    ) -> Result<(usize, usize), String> {
+        let mut total_pulled = 0;
+        let mut total_conflicts = 0;
+        let page_size = batch_size.max(1);
+
+        loop {
+            let after = self.get_checkpoint()?;
+            let (records, cursor) = self.pull_page(server_url, account_id, after, page_size).await?;
+            if records.is_empty() {
+                break;
+            }
+            let page_len = records.len();
+
+            let (conflicted, clean): (Vec<SyncRecord>, Vec<SyncRecord>) = records
+                .into_iter()
+                .map(|r| SyncRecord {
+                    table_name: r.table_name,
+                    row_id: r.row_id,
+                    data: r.data,
+                    version: r.version,
+                    deleted: r.deleted,
+                })
+                .partition(|r| {
+                    pushed_changes.iter().any(|pc| {
+                        pc.record.table_name == r.table_name
+                            && pc.record.row_id == r.row_id
+                            && pc.record.version != r.version
+                    })
+                });
+
+            total_conflicts += conflicted.len();
+            if !clean.is_empty() {
+                self.apply_remote_changes(&clean, key)?;
+            }
+            if !conflicted.is_empty() {
+                self.resolve_conflicts(pushed_changes, &conflicted, key)?;
+            }
+
+            total_pulled += page_len;
+            self.save_checkpoint(cursor)?;
+
+            if page_len < page_size {
+                break;
+            }
+        }
+
+        Ok((total_pulled, total_conflicts))
+    }
+
+    /// POST a batch of local changes to `/api/history`, returning how many the
+    /// server actually applied. The response only carries a count, not which
+    /// records it covers, so the caller marks synced the same first-`synced_count`
+    /// prefix of the batch the server iterates in - conservative if the server
+    /// skipped an earlier row, but never marks a rejected row synced before a
+    /// later one in the same batch.
+    async fn push_batch(
+        &self,
+        server_url: &str,
+        account_id: &str,
+        batch: &[PendingChange],
+    ) -> Result<usize, String> {
+        if batch.is_empty() {
+            return Ok(0);
+        }
+        let records = batch
+            .iter()
+            .map(|pc| HistoryPushRecord {
+                table_name: pc.record.table_name.clone(),
+                row_id: pc.record.row_id.clone(),
+                data: pc.record.data.clone(),
+                version: pc.record.version,
+                deleted: pc.record.deleted,
+            })
+            .collect();
+
+        let response: HistoryPushResponseBody = self
+            .http
+            .post(format!("{}/api/history", server_url))
+            .bearer_auth(account_id)
+            .json(&HistoryPushRequestBody { records })
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .error_for_status()
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(response.synced_count)
+    }
+
+    /// GET one page of records newer than `after` from `/api/history`, returning
+    /// the records and the cursor the next page should resume from.
+    async fn pull_page(
+        &self,
+        server_url: &str,
+        account_id: &str,
+        after: i64,
+        limit: usize,
+    ) -> Result<(Vec<HistoryPullRecord>, i64), String> {
+        let response: HistoryPullResponseBody = self
+            .http
+            .get(format!("{}/api/history", server_url))
+            .bearer_auth(account_id)
+            .query(&[("after", after.to_string()), ("limit", limit.to_string())])
+            .send()
+            .await
+            .map_err(|e| e.to_string())?
+            .error_for_status()
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok((response.records, response.cursor))
+    }
+
     /// Get current sync status
     #[tauri::command]
     pub async fn get_sync_status() -> Result<SyncStatus, String> {
@@ -148,21 +447,45 @@ impl SyncService {
 
         Ok(SyncStatus {
             configured: true, // Always true if we can access the DB
-            authenticated: false, // Determined by caller (has tokens?)
+            authenticated: false, // Determined by caller (has configured sync?)
             last_sync_at,
             pending_changes,
             server_url: None,
         })
     }
 
-    /// Configure sync settings
+    /// Configure background sync. Passing `enabled: false` stops any running
+    /// background loop; `enabled: true` (re)starts it on `interval_seconds`,
+    /// waking to sync whenever there are pending changes.
     #[tauri::command]
     pub async fn configure_sync(
-        _server_url: String,
-        _app_id: String,
-        _api_key: String,
+        app: tauri::AppHandle,
+        state: tauri::State<'_, crate::AppState>,
+        server_url: String,
+        passphrase: String,
+        interval_seconds: u64,
+        enabled: bool,
     ) -> Result<(), String> {
-        // Configuration is handled at the app level (stored in Tauri store)
+        if enabled {
+            state.sync_scheduler.configure(
+                app,
+                interval_seconds,
+                crate::sync_scheduler::SyncCredentials { server_url, passphrase },
+            );
+        } else {
+            state.sync_scheduler.stop();
+        }
+        Ok(())
+    }
+
+    /// Wake the background sync loop immediately instead of waiting out the rest
+    /// of its interval. The frontend calls this on app-focus and
+    /// `navigator.onLine` events, so a device that was backgrounded or offline
+    /// syncs promptly once it's usable again. A no-op if background sync isn't
+    /// configured.
+    #[tauri::command]
+    pub async fn trigger_sync_check(state: tauri::State<'_, crate::AppState>) -> Result<(), String> {
+        state.sync_scheduler.nudge();
         Ok(())
     }
 
@@ -176,323 +499,205 @@ impl SyncService {
         Ok(())
     }
 
-    // ===== Private helper methods =====
-
-    fn collect_local_changes(&self) -> Result<Vec<SyncRecord>, String> {
-        let mut records = Vec::new();
-
-        // Collect deleted sessions
-        let deleted_sessions = self.query_deleted_sessions()?;
-        for session in deleted_sessions {
-            records.push(SyncRecord {
-                table_name: "session_history".to_string(),
-                row_id: session.id,
-                data: serde_json::json!({}),
-                version: session.sync_version,
-                deleted: true,
-            });
-        }
-
-        // Collect active unsynced sessions
-        let sessions = self.query_unsynced_sessions()?;
-        for session in sessions {
-            records.push(self.session_to_sync_record(&session, false)?);
-        }
-
-        Ok(records)
+    /// Export the locally stored Argon2id salt so a second device can join the same
+    /// encrypted account. The salt alone isn't the key - whoever imports it still
+    /// needs the same passphrase to re-derive it via [`sync_crypto::derive_key`] -
+    /// so this is safe to write to a file or hand to a pairing flow without itself
+    /// granting access to any existing synced data.
+    #[tauri::command]
+    pub async fn export_encryption_key() -> Result<EncryptionKeyBundle, String> {
+        let db = Database::new(std::path::Path::new("immergo.db")).map_err(|e| e.to_string())?;
+        let service = SyncService::new(db);
+        let salt = service.get_or_create_encryption_salt()?;
+        Ok(EncryptionKeyBundle {
+            version: sync_crypto::KEY_VERSION,
+            salt: BASE64.encode(salt),
+        })
     }
 
-    fn query_deleted_sessions(&self) -> Result<Vec<SessionHistory>, String> {
-        let mut stmt = self
-            .db
-            .conn
-            .prepare(
-                "SELECT id, mission_json, language, from_language, mode, voice, result_json,
-                        completed_at, sync_version, synced_at, deleted, deleted_at
-                 FROM session_history
-                 WHERE deleted = 1 AND synced_at IS NULL",
+    /// Re-import a salt exported by [`Self::export_encryption_key`] from another
+    /// device, so this device derives the same key from the shared passphrase
+    /// instead of generating its own on next sync.
+    #[tauri::command]
+    pub async fn import_encryption_key(bundle: EncryptionKeyBundle) -> Result<(), String> {
+        if bundle.version != sync_crypto::KEY_VERSION {
+            return Err(format!("Unsupported encryption key bundle version: {}", bundle.version));
+        }
+        let db = Database::new(std::path::Path::new("immergo.db")).map_err(|e| e.to_string())?;
+        db.conn
+            .execute(
+                "INSERT OR REPLACE INTO sync_metadata (table_name, last_sync_timestamp, cursor)
+                 VALUES ('encryption_salt', 0, ?1)",
+                params![bundle.salt],
             )
             .map_err(|e| e.to_string())?;
-
-        let sessions = stmt
-            .query_map([], |row| {
-                Ok(SessionHistory {
-                    id: row.get(0)?,
-                    mission_json: row.get(1)?,
-                    language: row.get(2)?,
-                    from_language: row.get(3)?,
-                    mode: row.get(4)?,
-                    voice: row.get(5)?,
-                    result_json: row.get(6)?,
-                    completed_at: row.get(7)?,
-                    sync_version: row.get(8)?,
-                    synced_at: row.get(9)?,
-                    deleted: row.get::<_, i64>(10)? == 1,
-                    deleted_at: row.get(11)?,
-                })
-            })
-            .map_err(|e| e.to_string())?;
-
-        sessions.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+        Ok(())
     }
 
-    fn query_unsynced_sessions(&self) -> Result<Vec<SessionHistory>, String> {
-        let mut stmt = self
-            .db
-            .conn
-            .prepare(
-                "SELECT id, mission_json, language, from_language, mode, voice, result_json,
-                        completed_at, sync_version, synced_at, deleted, deleted_at
-                 FROM session_history
-                 WHERE deleted = 0 AND synced_at IS NULL",
-            )
-            .map_err(|e| e.to_string())?;
+    // ===== Private helper methods =====
 
-        let sessions = stmt
-            .query_map([], |row| {
-                Ok(SessionHistory {
-                    id: row.get(0)?,
-                    mission_json: row.get(1)?,
-                    language: row.get(2)?,
-                    from_language: row.get(3)?,
-                    mode: row.get(4)?,
-                    voice: row.get(5)?,
-                    result_json: row.get(6)?,
-                    completed_at: row.get(7)?,
-                    sync_version: row.get(8)?,
-                    synced_at: row.get(9)?,
-                    deleted: row.get::<_, i64>(10)? == 1,
-                    deleted_at: row.get(11)?,
-                })
-            })
-            .map_err(|e| e.to_string())?;
+    /// Fetch the locally stored Argon2id salt, generating and persisting a fresh one
+    /// on first use. Only the salt ever touches disk - the passphrase and the key
+    /// derived from it do not.
+    fn get_or_create_encryption_salt(&self) -> Result<Vec<u8>, String> {
+        let result: Result<String, _> = self.db.conn.query_row(
+            "SELECT cursor FROM sync_metadata WHERE table_name = 'encryption_salt'",
+            [],
+            |row| row.get(0),
+        );
 
-        sessions.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+        match result {
+            Ok(salt_b64) => BASE64.decode(&salt_b64).map_err(|e| e.to_string()),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                let salt = sync_crypto::generate_salt();
+                self.db
+                    .conn
+                    .execute(
+                        "INSERT OR REPLACE INTO sync_metadata (table_name, last_sync_timestamp, cursor)
+                         VALUES ('encryption_salt', 0, ?1)",
+                        params![BASE64.encode(salt)],
+                    )
+                    .map_err(|e| e.to_string())?;
+                Ok(salt.to_vec())
+            }
+            Err(e) => Err(e.to_string()),
+        }
     }
 
-    fn session_to_sync_record(
-        &self,
-        session: &SessionHistory,
-        deleted: bool,
-    ) -> Result<SyncRecord, String> {
-        let mut data = serde_json::json!({
-            "mission_json": session.mission_json,
-            "language": session.language,
-            "from_language": session.from_language,
-            "mode": session.mode,
-            "voice": session.voice,
-            "result_json": session.result_json,
-            "completed_at": session.completed_at,
-        });
-
-        // Remove null fields
-        if let Some(obj) = data.as_object_mut() {
-            obj.retain(|_, v| !v.is_null());
+    /// Read every registered table's coalesced change log and turn each entry into
+    /// the `SyncRecord` it becomes on the wire.
+    fn collect_local_changes(&self, key: &[u8; 32]) -> Result<Vec<PendingChange>, String> {
+        let mut changes = Vec::new();
+        for table in &self.tables {
+            for entry in change_log::coalesced_unsynced(&self.db, table.table_name())? {
+                let record = table.to_sync_record(&entry, key)?;
+                changes.push(PendingChange { record, seq: entry.seq });
+            }
         }
-
-        Ok(SyncRecord {
-            table_name: "session_history".to_string(),
-            row_id: session.id.clone(),
-            data,
-            version: session.sync_version,
-            deleted,
-        })
+        Ok(changes)
     }
 
-    fn apply_remote_changes(&self, records: &[SyncRecord]) -> Result<(), String> {
+    fn apply_remote_changes(&self, records: &[SyncRecord], key: &[u8; 32]) -> Result<(), String> {
         for record in records {
-            if record.table_name != "session_history" {
-                eprintln!("Unknown table: {}", record.table_name);
-                continue;
-            }
-
-            if record.deleted {
-                // Hard delete
-                self.hard_delete_session(&record.row_id)?;
-            } else {
-                // Upsert
-                let session = self.sync_record_to_session(record)?;
-                let exists = self.session_exists(&record.row_id)?;
-
-                if exists {
-                    self.update_session(&session)?;
-                } else {
-                    self.create_session(&session)?;
-                }
+            match self.table_for(&record.table_name) {
+                Some(table) => table.apply(&self.db, record, key)?,
+                None => eprintln!("Unknown sync table: {}", record.table_name),
             }
         }
         Ok(())
     }
 
-    fn sync_record_to_session(&self, record: &SyncRecord) -> Result<SessionHistory, String> {
-        let data = &record.data;
-        let now = chrono::Utc::now().timestamp();
-
-        Ok(SessionHistory {
-            id: record.row_id.clone(),
-            mission_json: data.get("mission_json").and_then(|v| v.as_str()).map(String::from),
-            language: data
-                .get("language")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing language")?
-                .to_string(),
-            from_language: data
-                .get("from_language")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing from_language")?
-                .to_string(),
-            mode: data
-                .get("mode")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing mode")?
-                .to_string(),
-            voice: data
-                .get("voice")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing voice")?
-                .to_string(),
-            result_json: data
-                .get("result_json")
-                .and_then(|v| v.as_str())
-                .ok_or("Missing result_json")?
-                .to_string(),
-            completed_at: data
-                .get("completed_at")
-                .and_then(|v| v.as_i64())
-                .ok_or("Missing completed_at")?,
-            sync_version: record.version,
-            synced_at: Some(now),
-            deleted: false,
-            deleted_at: None,
-        })
-    }
+    /// Reconcile rows the server rejected from our push because its version had
+    /// already moved on. Policy is last-write-wins keyed on `completed_at`, with the
+    /// server winning ties; a deletion on either side always wins outright. The
+    /// losing copy is archived to `sync_conflicts` so the UI can surface it.
+    fn resolve_conflicts(
+        &self,
+        local_changes: &[PendingChange],
+        remote_conflicts: &[SyncRecord],
+        key: &[u8; 32],
+    ) -> Result<(), String> {
+        let resolved_at = chrono::Utc::now().timestamp();
 
-    fn session_exists(&self, id: &str) -> Result<bool, String> {
-        let count: i64 = self
-            .db
-            .conn
-            .query_row(
-                "SELECT COUNT(*) FROM session_history WHERE id = ?1",
-                params![id],
-                |row| row.get(0),
-            )
-            .map_err(|e| e.to_string())?;
-        Ok(count > 0)
-    }
+        for remote in remote_conflicts {
+            let Some(local) = local_changes
+                .iter()
+                .find(|pc| pc.record.row_id == remote.row_id && pc.record.table_name == remote.table_name)
+            else {
+                continue;
+            };
+            let Some(table) = self.table_for(&remote.table_name) else {
+                eprintln!("Unknown sync table: {}", remote.table_name);
+                continue;
+            };
+
+            let resolved_side = table.resolve_conflict(&self.db, &local.record, remote, key)?;
+            self.record_conflict(&local.record, remote, resolved_side, resolved_at, key)?;
+
+            // A remote win supersedes the pending local edit, so its change-log
+            // entry is retired rather than re-pushed over the value that just won.
+            // A local win leaves it unsynced so the next round retries the push.
+            if resolved_side == "remote" {
+                change_log::mark_synced(&self.db, &local.record.table_name, &local.record.row_id, local.seq)?;
+            }
+        }
 
-    fn create_session(&self, session: &SessionHistory) -> Result<(), String> {
-        self.db
-            .conn
-            .execute(
-                "INSERT INTO session_history (id, mission_json, language, from_language, mode, voice,
-                 result_json, completed_at, sync_version, synced_at, deleted, deleted_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
-                params![
-                    session.id,
-                    session.mission_json,
-                    session.language,
-                    session.from_language,
-                    session.mode,
-                    session.voice,
-                    session.result_json,
-                    session.completed_at,
-                    session.sync_version,
-                    session.synced_at,
-                    if session.deleted { 1 } else { 0 },
-                    session.deleted_at,
-                ],
-            )
-            .map_err(|e| e.to_string())?;
         Ok(())
     }
 
-    fn update_session(&self, session: &SessionHistory) -> Result<(), String> {
+    /// Archive both sides of a conflict into `sync_conflicts` for the UI to surface,
+    /// e.g. "your local copy of this session was overwritten."
+    fn record_conflict(
+        &self,
+        local: &SyncRecord,
+        remote: &SyncRecord,
+        resolved_side: &str,
+        resolved_at: i64,
+        key: &[u8; 32],
+    ) -> Result<(), String> {
+        let describe = |record: &SyncRecord| -> Result<String, String> {
+            if record.deleted {
+                Ok(serde_json::json!({ "deleted": true }).to_string())
+            } else {
+                Ok(sync_crypto::decrypt(key, &record.data)?.to_string())
+            }
+        };
+
         self.db
             .conn
             .execute(
-                "UPDATE session_history
-                 SET mission_json = ?2, language = ?3, from_language = ?4, mode = ?5, voice = ?6,
-                     result_json = ?7, completed_at = ?8, sync_version = ?9, synced_at = ?10,
-                     deleted = ?11, deleted_at = ?12
-                 WHERE id = ?1",
+                "INSERT INTO sync_conflicts (id, table_name, local_json, remote_json, resolved_side, resolved_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
                 params![
-                    session.id,
-                    session.mission_json,
-                    session.language,
-                    session.from_language,
-                    session.mode,
-                    session.voice,
-                    session.result_json,
-                    session.completed_at,
-                    session.sync_version,
-                    session.synced_at,
-                    if session.deleted { 1 } else { 0 },
-                    session.deleted_at,
+                    local.row_id,
+                    local.table_name,
+                    describe(local)?,
+                    describe(remote)?,
+                    resolved_side,
+                    resolved_at,
                 ],
             )
             .map_err(|e| e.to_string())?;
         Ok(())
     }
 
-    fn hard_delete_session(&self, id: &str) -> Result<(), String> {
-        self.db
-            .conn
-            .execute("DELETE FROM session_history WHERE id = ?1", params![id])
-            .map_err(|e| e.to_string())?;
-        Ok(())
-    }
-
-    fn mark_records_synced(&self, records: &[SyncRecord], synced_at: i64) -> Result<(), String> {
-        for record in records {
-            if record.deleted {
-                self.hard_delete_session(&record.row_id)?;
-            } else {
-                self.db
-                    .conn
-                    .execute(
-                        "UPDATE session_history SET synced_at = ?1, sync_version = sync_version + 1 WHERE id = ?2",
-                        params![synced_at, record.row_id],
-                    )
-                    .map_err(|e| e.to_string())?;
-            }
+    fn mark_records_synced(&self, records: &[PendingChange]) -> Result<(), String> {
+        for pc in records {
+            change_log::mark_synced(&self.db, &pc.record.table_name, &pc.record.row_id, pc.seq)?;
         }
         Ok(())
     }
 
-    fn count_pending_changes(&self) -> Result<usize, String> {
-        let count: i64 = self
-            .db
-            .conn
-            .query_row(
-                "SELECT COUNT(*) FROM session_history WHERE synced_at IS NULL",
-                [],
-                |row| row.get(0),
-            )
-            .map_err(|e| e.to_string())?;
-        Ok(count as usize)
+    pub(crate) fn count_pending_changes(&self) -> Result<usize, String> {
+        change_log::count_pending(&self.db)
     }
 
-    fn get_checkpoint(&self) -> Result<Option<Checkpoint>, String> {
-        let result: Result<(String, String), _> = self.db.conn.query_row(
-            "SELECT last_sync_timestamp, cursor FROM sync_metadata WHERE table_name = 'checkpoint'",
+    /// The `after` cursor the next `/api/history` pull should resume from - the
+    /// greatest server-side `updated_at` timestamp seen in any prior pulled page.
+    fn get_checkpoint(&self) -> Result<i64, String> {
+        let result: Result<String, _> = self.db.conn.query_row(
+            "SELECT cursor FROM sync_metadata WHERE table_name = 'checkpoint'",
             [],
-            |row| Ok((row.get(0)?, row.get(1)?)),
+            |row| row.get(0),
         );
 
         match result {
-            Ok((updated_at, id)) => Ok(Some(Checkpoint { updated_at, id })),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            // A pre-REST-migration install may have left a non-numeric checkpoint
+            // id here; treat it the same as no checkpoint rather than failing every
+            // sync from then on, since re-pulling from the start is idempotent.
+            Ok(cursor) => Ok(cursor.parse::<i64>().unwrap_or(0)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(0),
             Err(e) => Err(e.to_string()),
         }
     }
 
-    fn save_checkpoint(&self, checkpoint: &Checkpoint) -> Result<(), String> {
+    fn save_checkpoint(&self, cursor: i64) -> Result<(), String> {
         self.db
             .conn
             .execute(
                 "INSERT OR REPLACE INTO sync_metadata (table_name, last_sync_timestamp, cursor)
                  VALUES ('checkpoint', ?1, ?2)",
-                params![checkpoint.updated_at, checkpoint.id],
+                params![chrono::Utc::now().timestamp(), cursor.to_string()],
             )
             .map_err(|e| e.to_string())?;
         Ok(())
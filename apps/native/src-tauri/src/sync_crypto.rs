@@ -0,0 +1,104 @@
+/**
+ * Copyright 2026 Google LLC
+ * Licensed under the Apache License, Version 2.0
+ */
+
+//! End-to-end encryption for synced session payloads.
+//!
+//! The sync server only ever sees ciphertext: a [`SyncRecord`](qm_sync_client::SyncRecord)'s
+//! `data` field is replaced with a versioned envelope encrypted under a key derived from
+//! the user's passphrase via Argon2id. `table_name`, `row_id`, `version`, and `deleted`
+//! stay in the clear since the delta protocol needs them for routing.
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Current envelope/key version. A future key rotation bumps this so records still
+/// encrypted under an old passphrase-derived key can be told apart from new ones.
+pub const KEY_VERSION: u8 = 1;
+
+/// A client-side-encrypted `SyncRecord.data` payload.
+#[derive(Debug, Serialize, Deserialize)]
+struct SyncEnvelope {
+    v: u8,
+    /// Base64-encoded 12-byte AES-GCM nonce.
+    iv: String,
+    /// Base64-encoded ciphertext with the GCM tag appended.
+    ct: String,
+}
+
+/// Derive a 32-byte AES-256-GCM key from a passphrase and salt using Argon2id.
+pub fn derive_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], String> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+/// Generate a fresh random salt for a new passphrase. Only this salt is persisted
+/// locally (in `sync_metadata`); the passphrase and derived key never are.
+pub fn generate_salt() -> [u8; 16] {
+    let mut salt = [0u8; 16];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// Derive the account id a paired device presents as `Authorization: Bearer
+/// <account_id>` on every `/api/history` call, as an HMAC-SHA256 of the locally
+/// stored salt keyed by the passphrase-derived encryption key. The salt by itself
+/// is deliberately not secret - it's persisted in the clear in `sync_metadata` and
+/// handed out unmodified by [`export_encryption_key`](crate::sync::SyncService::export_encryption_key)
+/// before the passphrase is ever shared - so it can't double as a sync credential.
+/// Keying the HMAC with `key` ties the account id to something only a device that
+/// also knows the passphrase can reproduce.
+pub fn account_id(key: &[u8; 32], salt: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts a key of any length");
+    mac.update(salt);
+    BASE64.encode(mac.finalize().into_bytes())
+}
+
+/// Encrypt `plaintext` into a versioned envelope under `key`, using a fresh random
+/// nonce per call.
+pub fn encrypt(key: &[u8; 32], plaintext: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let plaintext_bytes = serde_json::to_vec(plaintext).map_err(|e| e.to_string())?;
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext_bytes.as_ref())
+        .map_err(|e| format!("Encryption failed: {}", e))?;
+
+    serde_json::to_value(SyncEnvelope {
+        v: KEY_VERSION,
+        iv: BASE64.encode(nonce),
+        ct: BASE64.encode(ciphertext),
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Decrypt an envelope produced by [`encrypt`] back into its plaintext JSON value.
+pub fn decrypt(key: &[u8; 32], data: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let envelope: SyncEnvelope =
+        serde_json::from_value(data.clone()).map_err(|e| format!("Malformed sync envelope: {}", e))?;
+    if envelope.v != KEY_VERSION {
+        return Err(format!("Unsupported sync envelope version: {}", envelope.v));
+    }
+
+    let nonce_bytes = BASE64.decode(&envelope.iv).map_err(|e| e.to_string())?;
+    let ciphertext = BASE64.decode(&envelope.ct).map_err(|e| e.to_string())?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Nonce::from_slice(&nonce_bytes);
+    let plaintext = cipher
+        .decrypt(nonce, ciphertext.as_ref())
+        .map_err(|e| format!("Decryption failed: {}", e))?;
+
+    serde_json::from_slice(&plaintext).map_err(|e| e.to_string())
+}
@@ -0,0 +1,167 @@
+/**
+ * Copyright 2026 Google LLC
+ * Licensed under the Apache License, Version 2.0
+ */
+
+//! Background sync loop.
+//!
+//! `sync_now` only ever ran when the frontend explicitly invoked it. `SyncScheduler`
+//! wakes on an interval, checks whether there's anything pending, and drives
+//! `SyncService::perform_sync` itself - retrying transient failures with
+//! exponential backoff and jitter, and stopping outright on an auth failure so the
+//! UI can prompt for re-authentication instead of retrying against an account the
+//! server will never accept.
+//!
+//! The interval is a ceiling, not the only wake source: `nudge` lets the frontend
+//! wake the loop early (e.g. on window focus or a `navigator.onLine` event) via the
+//! `trigger_sync_check` command, so a device that was offline or backgrounded
+//! syncs promptly instead of waiting out the rest of its interval.
+
+use crate::db::Database;
+use crate::sync::SyncService;
+use aes_gcm::aead::{rand_core::RngCore, OsRng};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+
+/// Backoff is capped at a few minutes so a prolonged outage doesn't leave the app
+/// retrying hourly once the server recovers.
+const MAX_BACKOFF_SECONDS: u64 = 240;
+
+/// Everything a background sync round needs. Held in memory only - the passphrase
+/// is never persisted, so background sync only runs for the lifetime of a session
+/// where the user has supplied it via `configure_sync`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncCredentials {
+    pub server_url: String,
+    pub passphrase: String,
+}
+
+struct SchedulerState {
+    handle: Option<tauri::async_runtime::JoinHandle<()>>,
+    wake: Arc<Notify>,
+}
+
+/// One instance lives in `AppState` for the app's lifetime. `configure` (re)starts
+/// the background loop, cancelling any loop already running; `stop` cancels it.
+pub struct SyncScheduler {
+    state: Mutex<SchedulerState>,
+}
+
+impl SyncScheduler {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(SchedulerState { handle: None, wake: Arc::new(Notify::new()) }),
+        }
+    }
+
+    pub fn stop(&self) {
+        if let Some(handle) = self.state.lock().unwrap().handle.take() {
+            handle.abort();
+        }
+    }
+
+    pub fn configure(&self, app: AppHandle, interval_seconds: u64, credentials: SyncCredentials) {
+        self.stop();
+        let wake = Arc::new(Notify::new());
+        let handle = tauri::async_runtime::spawn(run_loop(
+            app,
+            credentials,
+            interval_seconds.max(1),
+            wake.clone(),
+        ));
+        let mut state = self.state.lock().unwrap();
+        state.wake = wake;
+        state.handle = Some(handle);
+    }
+
+    /// Wake the running loop immediately instead of waiting out the rest of its
+    /// interval. A no-op if no loop is running. Intended for the frontend to call
+    /// on app-focus / network-available events, where waiting for the next timer
+    /// tick would leave a backlog unsynced for longer than necessary.
+    pub fn nudge(&self) {
+        self.state.lock().unwrap().wake.notify_one();
+    }
+}
+
+impl Default for SyncScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn run_loop(
+    app: AppHandle,
+    credentials: SyncCredentials,
+    interval_seconds: u64,
+    wake: Arc<Notify>,
+) {
+    let mut backoff_attempt: u32 = 0;
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(interval_seconds)) => {}
+            _ = wake.notified() => {}
+        }
+
+        let db = match Database::new(std::path::Path::new("immergo.db")) {
+            Ok(db) => db,
+            Err(e) => {
+                eprintln!("Background sync: failed to open database: {}", e);
+                continue;
+            }
+        };
+        let service = SyncService::new(db);
+
+        match service.count_pending_changes() {
+            Ok(0) => continue,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("Background sync: failed to count pending changes: {}", e);
+                continue;
+            }
+        }
+
+        let result = service
+            .perform_sync(
+                &app,
+                &credentials.server_url,
+                &credentials.passphrase,
+                crate::sync::DEFAULT_BATCH_SIZE,
+            )
+            .await;
+
+        match result {
+            Ok(_) => {
+                backoff_attempt = 0;
+            }
+            Err(e) if is_auth_error(&e) => {
+                let _ = app.emit("sync-auth-required", e);
+                return;
+            }
+            Err(e) => {
+                backoff_attempt += 1;
+                let backoff = backoff_seconds(backoff_attempt);
+                let _ = app.emit("sync-error", e);
+                tokio::time::sleep(Duration::from_secs(backoff)).await;
+            }
+        }
+    }
+}
+
+/// Best-effort classification of an auth failure from the error string
+/// `perform_sync` returns. Anything else is treated as transient and retried.
+fn is_auth_error(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    lower.contains("unauthorized") || lower.contains("401") || lower.contains("invalid token") || lower.contains("auth")
+}
+
+/// `2^attempt` seconds capped at [`MAX_BACKOFF_SECONDS`], plus up to 50% jitter so a
+/// fleet of clients that failed at the same moment don't all retry in lockstep.
+fn backoff_seconds(attempt: u32) -> u64 {
+    let base = 1u64.checked_shl(attempt.min(8)).unwrap_or(MAX_BACKOFF_SECONDS).min(MAX_BACKOFF_SECONDS);
+    let jitter = u64::from(OsRng.next_u32()) % (base / 2 + 1);
+    base + jitter
+}
@@ -0,0 +1,345 @@
+/**
+ * Copyright 2026 Google LLC
+ * Licensed under the Apache License, Version 2.0
+ */
+
+//! Pluggable per-table sync support.
+//!
+//! `SyncService` used to hardcode `session_history` into `collect_local_changes`,
+//! `apply_remote_changes`, and `sync_record_to_session`, and silently dropped any
+//! other `table_name` it saw come back from the server. `SyncedTable` pulls that
+//! table-specific logic out behind a small trait so new syncable data (vocabulary
+//! decks, user settings, SRS review state, ...) can opt in by implementing it and
+//! registering in [`registry`], instead of copy-pasting the query/upsert helpers.
+//! Each implementer turns a coalesced [`ChangeLogEntry`] into a `SyncRecord` to
+//! push and knows how to apply one pulled from the server - it no longer decides
+//! *which* rows are unsynced, since [`crate::change_log`] already owns that.
+
+use crate::change_log::ChangeLogEntry;
+use crate::db::Database;
+use crate::sync_crypto;
+use qm_sync_client::SyncRecord;
+use rusqlite::params;
+
+#[derive(Debug, Clone)]
+pub struct SessionHistory {
+    pub id: String,
+    pub mission_json: Option<String>,
+    pub language: String,
+    pub from_language: String,
+    pub mode: String,
+    pub voice: String,
+    pub result_json: String,
+    pub completed_at: i64,
+}
+
+/// A table that can push its locally logged mutations into a sync delta and accept
+/// rows pulled back from the server. Implementations own their table's schema and
+/// row-mapping; `SyncService` only ever deals in opaque [`SyncRecord`]s and
+/// `table_name()` routing.
+pub trait SyncedTable: Send + Sync {
+    /// Name used in `SyncRecord::table_name` to route records to this implementer,
+    /// and to tag this table's rows in `change_log`.
+    fn table_name(&self) -> &'static str;
+
+    /// Turn one coalesced change-log entry into the `SyncRecord` to push,
+    /// encrypting a live payload under `key`. A `"delete"` op becomes a tombstone
+    /// with no payload.
+    fn to_sync_record(&self, entry: &ChangeLogEntry, key: &[u8; 32]) -> Result<SyncRecord, String>;
+
+    /// Apply a record pulled from the server: upsert if live, hard-delete if a
+    /// tombstone.
+    fn apply(&self, db: &Database, record: &SyncRecord, key: &[u8; 32]) -> Result<(), String>;
+
+    /// Reconcile one conflicting row using last-write-wins, applying whichever side
+    /// wins, and return which side won (`"local"` or `"remote"`) for the audit log.
+    fn resolve_conflict(
+        &self,
+        db: &Database,
+        local: &SyncRecord,
+        remote: &SyncRecord,
+        key: &[u8; 32],
+    ) -> Result<&'static str, String>;
+}
+
+/// All tables currently opted into sync. `session_history` is the first and, for
+/// now, only implementer.
+pub fn registry() -> Vec<Box<dyn SyncedTable>> {
+    vec![Box::new(SessionHistoryTable)]
+}
+
+pub struct SessionHistoryTable;
+
+impl SessionHistoryTable {
+    fn exists(&self, db: &Database, id: &str) -> Result<bool, String> {
+        let count: i64 = db
+            .conn
+            .query_row("SELECT COUNT(*) FROM session_history WHERE id = ?1", params![id], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        Ok(count > 0)
+    }
+
+    fn upsert(&self, db: &Database, session: &SessionHistory) -> Result<(), String> {
+        if self.exists(db, &session.id)? {
+            db.conn
+                .execute(
+                    "UPDATE session_history
+                     SET mission_json = ?2, language = ?3, from_language = ?4, mode = ?5, voice = ?6,
+                         result_json = ?7, completed_at = ?8, synced_at = ?9, deleted = 0, deleted_at = NULL
+                     WHERE id = ?1",
+                    params![
+                        session.id,
+                        session.mission_json,
+                        session.language,
+                        session.from_language,
+                        session.mode,
+                        session.voice,
+                        session.result_json,
+                        session.completed_at,
+                        chrono::Utc::now().timestamp(),
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+        } else {
+            db.conn
+                .execute(
+                    "INSERT INTO session_history (id, mission_json, language, from_language, mode, voice,
+                     result_json, completed_at, sync_version, synced_at, deleted, deleted_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 1, ?9, 0, NULL)",
+                    params![
+                        session.id,
+                        session.mission_json,
+                        session.language,
+                        session.from_language,
+                        session.mode,
+                        session.voice,
+                        session.result_json,
+                        session.completed_at,
+                        chrono::Utc::now().timestamp(),
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn hard_delete(&self, db: &Database, id: &str) -> Result<(), String> {
+        db.conn
+            .execute("DELETE FROM session_history WHERE id = ?1", params![id])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn decrypt_payload(&self, id: &str, key: &[u8; 32], data: &serde_json::Value) -> Result<SessionHistory, String> {
+        let data = sync_crypto::decrypt(key, data)?;
+        Self::session_from_json(id, &data)
+    }
+
+    fn session_from_json(id: &str, data: &serde_json::Value) -> Result<SessionHistory, String> {
+        Ok(SessionHistory {
+            id: id.to_string(),
+            mission_json: data.get("mission_json").and_then(|v| v.as_str()).map(String::from),
+            language: data.get("language").and_then(|v| v.as_str()).ok_or("Missing language")?.to_string(),
+            from_language: data
+                .get("from_language")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing from_language")?
+                .to_string(),
+            mode: data.get("mode").and_then(|v| v.as_str()).ok_or("Missing mode")?.to_string(),
+            voice: data.get("voice").and_then(|v| v.as_str()).ok_or("Missing voice")?.to_string(),
+            result_json: data
+                .get("result_json")
+                .and_then(|v| v.as_str())
+                .ok_or("Missing result_json")?
+                .to_string(),
+            completed_at: data.get("completed_at").and_then(|v| v.as_i64()).ok_or("Missing completed_at")?,
+        })
+    }
+}
+
+impl SyncedTable for SessionHistoryTable {
+    fn table_name(&self) -> &'static str {
+        "session_history"
+    }
+
+    fn to_sync_record(&self, entry: &ChangeLogEntry, key: &[u8; 32]) -> Result<SyncRecord, String> {
+        match entry.op.as_str() {
+            "delete" => Ok(SyncRecord {
+                table_name: self.table_name().to_string(),
+                row_id: entry.row_id.clone(),
+                data: serde_json::json!({}),
+                version: entry.seq,
+                deleted: true,
+            }),
+            _ => {
+                let payload = entry
+                    .payload_json
+                    .as_deref()
+                    .ok_or("Upsert change-log entry missing payload_json")?;
+                let data: serde_json::Value = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+                Ok(SyncRecord {
+                    table_name: self.table_name().to_string(),
+                    row_id: entry.row_id.clone(),
+                    data: sync_crypto::encrypt(key, &data)?,
+                    version: entry.seq,
+                    deleted: false,
+                })
+            }
+        }
+    }
+
+    fn apply(&self, db: &Database, record: &SyncRecord, key: &[u8; 32]) -> Result<(), String> {
+        if record.deleted {
+            self.hard_delete(db, &record.row_id)
+        } else {
+            let session = self.decrypt_payload(&record.row_id, key, &record.data)?;
+            self.upsert(db, &session)
+        }
+    }
+
+    fn resolve_conflict(
+        &self,
+        db: &Database,
+        local: &SyncRecord,
+        remote: &SyncRecord,
+        key: &[u8; 32],
+    ) -> Result<&'static str, String> {
+        if local.deleted || remote.deleted {
+            return if remote.deleted {
+                self.hard_delete(db, &remote.row_id)?;
+                Ok("remote")
+            } else {
+                // Local deletion wins; nothing to apply; the next sync round
+                // re-pushes the still-unsynced delete entry from the change log.
+                Ok("local")
+            };
+        }
+
+        let local_session = self.decrypt_payload(&local.row_id, key, &local.data)?;
+        let remote_session = self.decrypt_payload(&remote.row_id, key, &remote.data)?;
+
+        if local_session.completed_at > remote_session.completed_at {
+            // Local wins; the unsynced change-log entry is re-pushed next round.
+            Ok("local")
+        } else {
+            self.upsert(db, &remote_session)?;
+            Ok("remote")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    const TEST_KEY: [u8; 32] = [7u8; 32];
+
+    fn test_db() -> Database {
+        Database::new(Path::new(":memory:")).unwrap()
+    }
+
+    fn session_record(row_id: &str, completed_at: i64) -> SyncRecord {
+        let data = serde_json::json!({
+            "mission_json": null,
+            "language": "es",
+            "from_language": "en",
+            "mode": "conversation",
+            "voice": "Aoede",
+            "result_json": "{}",
+            "completed_at": completed_at,
+        });
+        SyncRecord {
+            table_name: "session_history".to_string(),
+            row_id: row_id.to_string(),
+            data: sync_crypto::encrypt(&TEST_KEY, &data).unwrap(),
+            version: 1,
+            deleted: false,
+        }
+    }
+
+    fn tombstone(row_id: &str) -> SyncRecord {
+        SyncRecord {
+            table_name: "session_history".to_string(),
+            row_id: row_id.to_string(),
+            data: serde_json::json!({}),
+            version: 2,
+            deleted: true,
+        }
+    }
+
+    #[test]
+    fn resolve_conflict_newer_completed_at_wins_remote() {
+        let db = test_db();
+        let table = SessionHistoryTable;
+        let local = session_record("s1", 100);
+        let remote = session_record("s1", 200);
+
+        let winner = table.resolve_conflict(&db, &local, &remote, &TEST_KEY).unwrap();
+
+        assert_eq!(winner, "remote");
+        assert!(table.exists(&db, "s1").unwrap());
+    }
+
+    #[test]
+    fn resolve_conflict_newer_completed_at_wins_local() {
+        let db = test_db();
+        let table = SessionHistoryTable;
+        let local = session_record("s1", 200);
+        let remote = session_record("s1", 100);
+
+        let winner = table.resolve_conflict(&db, &local, &remote, &TEST_KEY).unwrap();
+
+        assert_eq!(winner, "local");
+        // Local wins by not applying anything locally; the row stays absent here
+        // since this test never separately upserted the local row into `db`.
+        assert!(!table.exists(&db, "s1").unwrap());
+    }
+
+    #[test]
+    fn resolve_conflict_remote_tombstone_always_wins() {
+        let db = test_db();
+        let table = SessionHistoryTable;
+        table.upsert(&db, &SessionHistory {
+            id: "s1".to_string(),
+            mission_json: None,
+            language: "es".to_string(),
+            from_language: "en".to_string(),
+            mode: "conversation".to_string(),
+            voice: "Aoede".to_string(),
+            result_json: "{}".to_string(),
+            completed_at: 500,
+        }).unwrap();
+        let local = session_record("s1", 500);
+        let remote = tombstone("s1");
+
+        let winner = table.resolve_conflict(&db, &local, &remote, &TEST_KEY).unwrap();
+
+        assert_eq!(winner, "remote");
+        assert!(!table.exists(&db, "s1").unwrap());
+    }
+
+    #[test]
+    fn resolve_conflict_local_tombstone_wins_without_deleting_remote_copy() {
+        let db = test_db();
+        let table = SessionHistoryTable;
+        let local = tombstone("s1");
+        let remote = session_record("s1", 500);
+
+        let winner = table.resolve_conflict(&db, &local, &remote, &TEST_KEY).unwrap();
+
+        assert_eq!(winner, "local");
+        assert!(!table.exists(&db, "s1").unwrap());
+    }
+
+    #[test]
+    fn apply_upserts_a_live_record_then_hard_deletes_on_tombstone() {
+        let db = test_db();
+        let table = SessionHistoryTable;
+        table.apply(&db, &session_record("s1", 100), &TEST_KEY).unwrap();
+        assert!(table.exists(&db, "s1").unwrap());
+
+        table.apply(&db, &tombstone("s1"), &TEST_KEY).unwrap();
+        assert!(!table.exists(&db, "s1").unwrap());
+    }
+}
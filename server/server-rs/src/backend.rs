@@ -0,0 +1,80 @@
+//! Pluggable realtime-voice backend abstraction.
+//!
+//! `handle_socket_inner` used to construct a concrete `GeminiLiveClient` directly,
+//! which meant the axum/session layer was hard-wired to Gemini. `RealtimeBackend`
+//! captures the contract that was already implicit there - built from config, the
+//! client's setup payload, and the `audio_rx`/`text_rx`/`event_tx` channels, then
+//! driven to completion with `run()` - so the WebSocket front-end and session/token
+//! machinery can drive any upstream that implements it.
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::{
+    config::Config, gemini::GeminiLiveClient, handlers::websocket::ClientEvent, state::AppState,
+};
+
+/// A realtime voice backend that proxies a single `/ws` session to some upstream
+/// conversational API.
+#[async_trait]
+pub trait RealtimeBackend: Send {
+    /// Short identifier reported to clients (e.g. via `/api/health`), e.g. `"gemini"`.
+    fn provider_name(&self) -> &'static str;
+
+    /// Drive the session to completion, forwarding upstream events via `event_tx`
+    /// until the upstream or the client closes the connection.
+    async fn run(self: Box<Self>) -> anyhow::Result<()>;
+}
+
+#[async_trait]
+impl RealtimeBackend for GeminiLiveClient {
+    fn provider_name(&self) -> &'static str {
+        "gemini"
+    }
+
+    async fn run(self: Box<Self>) -> anyhow::Result<()> {
+        GeminiLiveClient::run(*self).await
+    }
+}
+
+/// Build the configured `RealtimeBackend` for a new `/ws` session.
+///
+/// Unrecognized `config.provider` values fall back to Gemini rather than failing the
+/// connection, since Gemini remains the only implementation today.
+#[allow(clippy::too_many_arguments)]
+pub fn build_backend(
+    config: Config,
+    setup_config: Option<serde_json::Value>,
+    audio_rx: mpsc::Receiver<Vec<u8>>,
+    text_rx: mpsc::Receiver<String>,
+    event_tx: mpsc::Sender<ClientEvent>,
+    state: AppState,
+    session_token: String,
+    resume_handle: Option<String>,
+) -> Box<dyn RealtimeBackend> {
+    match config.provider.as_str() {
+        "gemini" => Box::new(GeminiLiveClient::new(
+            config,
+            setup_config,
+            audio_rx,
+            text_rx,
+            event_tx,
+            state,
+            session_token,
+            resume_handle,
+        )),
+        other => {
+            tracing::warn!("Unknown REALTIME_PROVIDER '{}', falling back to gemini", other);
+            Box::new(GeminiLiveClient::new(
+                config,
+                setup_config,
+                audio_rx,
+                text_rx,
+                event_tx,
+                state,
+                session_token,
+                resume_handle,
+            ))
+        }
+    }
+}
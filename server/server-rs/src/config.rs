@@ -30,6 +30,79 @@ pub struct Config {
 
     /// Input audio sample rate (Hz)
     pub input_sample_rate: u32,
+
+    /// How often the server sends a WebSocket Ping to the client (seconds)
+    pub ping_interval_seconds: u64,
+
+    /// How long to wait for a Pong (or any client frame) before treating the
+    /// connection as dead (seconds)
+    pub pong_timeout_seconds: u64,
+
+    /// How long the connection may go without any audio/text flowing in either
+    /// direction before the session is ended to stop burning Gemini quota (seconds)
+    pub inactivity_timeout_seconds: u64,
+
+    /// Which `RealtimeBackend` implementation to drive the `/ws` session with.
+    /// Currently only `"gemini"` is implemented; unrecognized values fall back to it.
+    pub provider: String,
+
+    /// Maximum number of times `GeminiLiveClient` will transparently reconnect after
+    /// a session/duration-limit close or a `goAway`, replaying the saved resumption
+    /// handle each time, before giving up and ending the session.
+    pub max_reconnect_attempts: u32,
+
+    /// Fallback input-transcription backend to tee the client's PCM audio into,
+    /// alongside (or instead of) Gemini's own transcription. `"none"` (the default)
+    /// uses Gemini's native transcription as-is; `"aws_transcribe"` runs every
+    /// session's audio through Amazon Transcribe streaming and replaces Gemini's
+    /// input captions with its results, for deployments where Gemini transcription
+    /// is unreliable or disabled.
+    pub transcription_backend: String,
+
+    /// Prometheus Pushgateway URL to push session metrics to on session end, when
+    /// built with the `metrics` cargo feature. `None` disables pushing; live gauges
+    /// (active sessions, etc.) are still exposed for in-process scraping either way.
+    pub metrics_pushgateway_url: Option<String>,
+
+    /// Job label attached to pushed metrics, identifying this server instance/deployment.
+    pub metrics_job_name: String,
+
+    /// Path to the SQLite database file session/turn history is persisted to.
+    pub session_db_path: String,
+
+    /// Soft per-session token-usage threshold. Crossing it emits a warning event to
+    /// the client but does not end the session. `None` (the default) disables the check.
+    pub token_budget_soft_limit: Option<u32>,
+
+    /// Hard per-session token-usage cap. Crossing it ends the session gracefully
+    /// instead of continuing to accrue cost. `None` (the default) disables the cap.
+    pub token_budget_hard_limit: Option<u32>,
+
+    /// Phrases that, when spoken by the model in full, halt its in-progress turn.
+    /// Empty (the default) disables stop-phrase detection entirely.
+    pub stop_sequences: Vec<String>,
+
+    /// Which `EmbeddingProvider` implementation embeds learner utterances for the
+    /// error-memory recall feature. `"none"` (the default) disables it entirely.
+    pub embedding_backend: String,
+
+    /// Minimum cosine similarity for a prior utterance to count as the same
+    /// recurring mistake as the one just made.
+    pub embedding_similarity_threshold: f32,
+
+    /// Minimum number of similar utterances (including the current one) seen before
+    /// a `review_suggestion` event is emitted for a recurring pattern.
+    pub embedding_cluster_min_size: usize,
+
+    /// Shared secret operators must present as `Authorization: Bearer <key>` to call
+    /// `/api/admin/*` routes. `None` (the default) fails every admin request closed
+    /// rather than leaving them open when nobody has set one.
+    pub admin_api_key: Option<String>,
+
+    /// Base64-encoded 32-byte Ed25519 seed for `SessionTokenIssuer`. `None` (the
+    /// default) falls back to a fresh in-process key each start, so scope tokens
+    /// minted by one instance won't verify on another and don't survive a restart.
+    pub session_token_signing_key: Option<String>,
 }
 
 impl Config {
@@ -45,8 +118,25 @@ impl Config {
     /// | `MODEL` | Gemini model name | `gemini-2.0-flash-live-001` |
     /// | `PORT` | Server port | `8000` |
     /// | `SESSION_TIME_LIMIT` | Max session seconds | `180` |
+    /// | `PING_INTERVAL_SECONDS` | Server keepalive ping interval | `15` |
+    /// | `PONG_TIMEOUT_SECONDS` | Max time to wait for a Pong before closing | `30` |
+    /// | `INACTIVITY_TIMEOUT_SECONDS` | Max time with no audio/text before closing | `300` |
+    /// | `REALTIME_PROVIDER` | Realtime backend to use (`gemini`) | `gemini` |
+    /// | `MAX_RECONNECT_ATTEMPTS` | Max transparent reconnects after a session-limit close/goAway | `3` |
+    /// | `TRANSCRIPTION_BACKEND` | Fallback input-transcription backend (`none`, `aws_transcribe`) | `none` |
+    /// | `METRICS_PUSHGATEWAY_URL` | Prometheus Pushgateway URL (requires the `metrics` feature) | None |
+    /// | `METRICS_JOB_NAME` | Job label for pushed metrics | `gemini-live-server` |
+    /// | `SESSION_DB_PATH` | SQLite file session/turn history is persisted to | `sessions.db` |
+    /// | `TOKEN_BUDGET_SOFT_LIMIT` | Cumulative session tokens that trigger a warning event | None |
+    /// | `TOKEN_BUDGET_HARD_LIMIT` | Cumulative session tokens that end the session | None |
+    /// | `STOP_SEQUENCES` | Comma-separated phrases that halt the model's turn when spoken | (empty) |
+    /// | `EMBEDDING_BACKEND` | Embedding backend for error-memory recall (`none`, `gemini`) | `none` |
+    /// | `EMBEDDING_SIMILARITY_THRESHOLD` | Minimum cosine similarity to count as a recurring mistake | `0.85` |
+    /// | `EMBEDDING_CLUSTER_MIN_SIZE` | Minimum occurrences before a review suggestion is emitted | `3` |
+    /// | `ADMIN_API_KEY` | Bearer key required by `/api/admin/*` routes | None (admin routes refuse all requests) |
+    /// | `SESSION_TOKEN_SIGNING_KEY` | Base64 32-byte Ed25519 seed for scope tokens | None (fresh key per process) |
     pub fn from_env() -> anyhow::Result<Self> {
-        Ok(Self {
+        let config = Self {
             api_key: env::var("GOOGLE_API_KEY")
                 .or_else(|_| env::var("GOOGLE_CLOUD_API_KEY"))
                 .ok(),
@@ -63,7 +153,52 @@ impl Config {
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(180),
             input_sample_rate: 16000,
-        })
+            ping_interval_seconds: env::var("PING_INTERVAL_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(15),
+            pong_timeout_seconds: env::var("PONG_TIMEOUT_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30),
+            inactivity_timeout_seconds: env::var("INACTIVITY_TIMEOUT_SECONDS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(300),
+            provider: env::var("REALTIME_PROVIDER").unwrap_or_else(|_| "gemini".to_string()),
+            max_reconnect_attempts: env::var("MAX_RECONNECT_ATTEMPTS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3),
+            transcription_backend: env::var("TRANSCRIPTION_BACKEND")
+                .unwrap_or_else(|_| "none".to_string()),
+            metrics_pushgateway_url: env::var("METRICS_PUSHGATEWAY_URL").ok(),
+            metrics_job_name: env::var("METRICS_JOB_NAME")
+                .unwrap_or_else(|_| "gemini-live-server".to_string()),
+            session_db_path: env::var("SESSION_DB_PATH")
+                .unwrap_or_else(|_| "sessions.db".to_string()),
+            token_budget_soft_limit: env::var("TOKEN_BUDGET_SOFT_LIMIT").ok().and_then(|s| s.parse().ok()),
+            token_budget_hard_limit: env::var("TOKEN_BUDGET_HARD_LIMIT").ok().and_then(|s| s.parse().ok()),
+            stop_sequences: env::var("STOP_SEQUENCES")
+                .ok()
+                .map(|s| s.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default(),
+            embedding_backend: env::var("EMBEDDING_BACKEND").unwrap_or_else(|_| "none".to_string()),
+            embedding_similarity_threshold: env::var("EMBEDDING_SIMILARITY_THRESHOLD")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0.85),
+            embedding_cluster_min_size: env::var("EMBEDDING_CLUSTER_MIN_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3),
+            admin_api_key: env::var("ADMIN_API_KEY").ok(),
+            session_token_signing_key: env::var("SESSION_TOKEN_SIGNING_KEY").ok(),
+        };
+
+        validate_stop_sequences(&config.stop_sequences)?;
+
+        Ok(config)
     }
 
     /// Build the Gemini Live API WebSocket URL.
@@ -91,3 +226,29 @@ impl Config {
         ))
     }
 }
+
+/// Reject a `stop_sequences` configuration where one phrase is a strict prefix of
+/// another (e.g. `["stop", "stop now"]`). `StopPhraseMatcher::push` matches the
+/// shortest complete phrase as soon as the streamed buffer reaches it, so an
+/// overlapping pair would cut a learner's turn off on the shorter phrase before
+/// the longer one they were actually saying could finish - silently, since nothing
+/// downstream would notice one stop phrase could never fire. Failing fast here
+/// means that ambiguity is caught at startup instead.
+fn validate_stop_sequences(sequences: &[String]) -> anyhow::Result<()> {
+    for (i, a) in sequences.iter().enumerate() {
+        for b in sequences.iter().skip(i + 1) {
+            if a == b {
+                continue;
+            }
+            if a.starts_with(b.as_str()) || b.starts_with(a.as_str()) {
+                anyhow::bail!(
+                    "STOP_SEQUENCES entries \"{}\" and \"{}\" overlap (one is a prefix of the other) - \
+                     the shorter phrase would always fire first and the longer one could never match",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,56 @@
+//! Bounded in-memory capture of `AppError::Internal` occurrences.
+//!
+//! These are bugs, not expected failure modes, so `tracing::error!` alone isn't enough
+//! for an operator to triage one after the fact without shipping logs somewhere. This
+//! keeps the last few error chains (plus a backtrace) in memory, exposed read-only via
+//! `GET /api/admin/diagnostics`, trading durability for zero extra infrastructure.
+
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+/// How many recent internal errors to retain; oldest is dropped once this is exceeded.
+const CAPACITY: usize = 50;
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// One captured `AppError::Internal`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticEntry {
+    pub occurred_at_ms: i64,
+    /// The error and its full `anyhow` cause chain, outermost first.
+    pub chain: Vec<String>,
+    pub backtrace: String,
+}
+
+static ENTRIES: Lazy<Mutex<VecDeque<DiagnosticEntry>>> = Lazy::new(|| Mutex::new(VecDeque::with_capacity(CAPACITY)));
+
+/// Record an `anyhow::Error` behind `AppError::Internal` for later inspection.
+pub fn record(err: &anyhow::Error) {
+    let entry = DiagnosticEntry {
+        occurred_at_ms: now_ms(),
+        chain: err.chain().map(|cause| cause.to_string()).collect(),
+        backtrace: err.backtrace().to_string(),
+    };
+
+    let mut entries = ENTRIES.lock().unwrap();
+    if entries.len() == CAPACITY {
+        entries.pop_front();
+    }
+    entries.push_back(entry);
+}
+
+/// Snapshot of everything currently retained, oldest first.
+pub fn recent() -> Vec<DiagnosticEntry> {
+    ENTRIES.lock().unwrap().iter().cloned().collect()
+}
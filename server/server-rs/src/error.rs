@@ -42,7 +42,10 @@ impl IntoResponse for AppError {
             AppError::WebSocketError(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
             AppError::GeminiError(msg) => (StatusCode::BAD_GATEWAY, msg.clone()),
             AppError::ConfigError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
-            AppError::Internal(err) => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
+            AppError::Internal(err) => {
+                crate::diagnostics::record(err);
+                (StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+            }
         };
 
         tracing::error!("Request error: {}", message);
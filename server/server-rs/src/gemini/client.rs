@@ -14,9 +14,14 @@ use tokio_tungstenite::{connect_async, tungstenite::Message};
 use crate::{
     config::Config,
     handlers::websocket::{ClientEvent, SessionStats},
+    state::AppState,
 };
 
+use super::embeddings::{build_embedding_provider, EmbeddingProvider};
 use super::messages::*;
+use super::tokenizer::Tokenizer;
+use super::tools::ToolRegistry;
+use super::transcription::build_transcriber;
 
 /// Safely truncate a UTF-8 string to approximately `max_chars` characters.
 /// This avoids panicking when slicing in the middle of multi-byte characters.
@@ -29,6 +34,132 @@ fn truncate_string(s: &str, max_chars: usize) -> String {
     }
 }
 
+/// An RFC 6051-style absolute media clock for one direction (input or output)
+/// of a session's audio stream, in milliseconds.
+///
+/// Each chunk of mono 16-bit PCM advances the clock by `bytes / 2 / sample_rate * 1000`
+/// ms. Audio/transcript events are stamped with the clock position so the client can
+/// align captions to the audio they describe instead of to wall-clock arrival time,
+/// which drifts under network jitter.
+struct MediaTimeline {
+    sample_rate: u32,
+    position_ms_bits: AtomicU64,
+}
+
+impl MediaTimeline {
+    fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            position_ms_bits: AtomicU64::new(0f64.to_bits()),
+        }
+    }
+
+    fn position_ms(&self) -> f64 {
+        f64::from_bits(self.position_ms_bits.load(Ordering::Relaxed))
+    }
+
+    /// Advance the clock by `bytes` of 16-bit PCM and return the `(start_ms, duration_ms)`
+    /// of this chunk on the timeline.
+    fn advance(&self, bytes: usize) -> (f64, f64) {
+        let start_ms = self.position_ms();
+        let duration_ms = (bytes as f64 / 2.0 / self.sample_rate as f64) * 1000.0;
+        self.position_ms_bits
+            .store((start_ms + duration_ms).to_bits(), Ordering::Relaxed);
+        (start_ms, duration_ms)
+    }
+
+    /// Reset the clock to zero, e.g. when Gemini cancels buffered output audio
+    /// on barge-in so downstream timestamps don't drift past the cut-off turn.
+    fn reset(&self) {
+        self.position_ms_bits.store(0f64.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Gemini outputs 24kHz mono 16-bit PCM; this is fixed by the Live API, not configurable.
+const GEMINI_OUTPUT_SAMPLE_RATE: u32 = 24000;
+
+/// Accumulates streaming transcript fragments for one direction (input or output)
+/// across a turn, so partial captions can be forwarded as they arrive instead of
+/// only once the whole segment is final.
+#[derive(Default)]
+struct TranscriptBuffer {
+    text: String,
+}
+
+impl TranscriptBuffer {
+    /// Merge a new fragment into the buffer. Gemini fragments are normally additive,
+    /// but a duplicate or already-seen fragment is dropped rather than appended again.
+    fn push(&mut self, fragment: &str) {
+        if fragment.is_empty() || self.text.ends_with(fragment) {
+            return;
+        }
+        self.text.push_str(fragment);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Take the accumulated text and reset the buffer for the next turn.
+    fn take(&mut self) -> String {
+        std::mem::take(&mut self.text)
+    }
+}
+
+/// What a fragment of output transcript should do once checked against the
+/// configured stop phrases.
+enum StopPhraseSignal {
+    /// Nothing is being held; `String` is the text to forward downstream as usual
+    /// (may combine more than one fragment that was briefly held as a candidate
+    /// prefix before turning out not to match anything).
+    Flush(String),
+    /// The buffer is still a strict prefix of some stop phrase; hold it and wait
+    /// for the next fragment instead of forwarding anything yet.
+    Holding,
+    /// The buffer exactly matched a configured stop phrase; the caller should
+    /// halt the model's turn.
+    Matched,
+}
+
+/// Incrementally matches streamed output-transcript fragments against a set of
+/// configured stop phrases, so a phrase like "let's move on" can halt the model's
+/// turn the moment it's spoken in full rather than only once the whole turn ends.
+#[derive(Default)]
+struct StopPhraseMatcher {
+    sequences: Vec<String>,
+    buffer: String,
+}
+
+impl StopPhraseMatcher {
+    fn new(sequences: Vec<String>) -> Self {
+        Self { sequences, buffer: String::new() }
+    }
+
+    /// Append `fragment` to the held buffer and decide what to do with it.
+    ///
+    /// Exact-match is checked before prefix-match. This assumes no configured phrase
+    /// is itself a prefix of another (e.g. `["stop", "stop now"]`) - `Config::from_env`
+    /// rejects that configuration at startup via `validate_stop_sequences`, since the
+    /// shorter phrase would otherwise fire as soon as the buffer reaches it, before
+    /// the next fragment could complete the longer one.
+    fn push(&mut self, fragment: &str) -> StopPhraseSignal {
+        if self.sequences.is_empty() {
+            return StopPhraseSignal::Flush(fragment.to_string());
+        }
+
+        self.buffer.push_str(fragment);
+
+        if self.sequences.iter().any(|s| s == &self.buffer) {
+            self.buffer.clear();
+            return StopPhraseSignal::Matched;
+        }
+        if self.sequences.iter().any(|s| s.starts_with(self.buffer.as_str())) {
+            return StopPhraseSignal::Holding;
+        }
+        StopPhraseSignal::Flush(std::mem::take(&mut self.buffer))
+    }
+}
+
 /// Gemini Live API client.
 ///
 /// Manages the WebSocket connection and message routing.
@@ -39,6 +170,47 @@ pub struct GeminiLiveClient {
     text_rx: mpsc::Receiver<String>,
     event_tx: mpsc::Sender<ClientEvent>,
     session_start: Instant,
+    /// Shared app state, used to persist/invalidate Gemini session-resumption handles.
+    state: AppState,
+    /// The session token this connection was authenticated with; doubles as the key
+    /// under which resumption handles are stored so a later `resume_token` can find them.
+    session_token: String,
+    /// A previously stored resumption handle to replay into the setup message, if any.
+    resume_handle: Option<String>,
+    /// Absolute media clock for audio Gemini sends us (24kHz output), used to stamp
+    /// `ClientEvent::Audio` and output transcripts.
+    output_timeline: Arc<MediaTimeline>,
+    /// Absolute media clock for audio we send Gemini (input sample rate), used to
+    /// stamp input transcripts.
+    input_timeline: Arc<MediaTimeline>,
+}
+
+/// What the connection loop for a single upstream WebSocket did, and what `run`
+/// should do next.
+enum ConnectionOutcome {
+    /// The session is over (client or Gemini ended it for good); stop looping.
+    Done,
+    /// Gemini hit a session/duration limit or sent a `goAway`; reconnect and replay
+    /// the carried resumption handle (if any) to continue the same conversation.
+    Reconnect(Option<String>),
+}
+
+/// Continuity-relevant signal extracted while handling one Gemini message.
+#[derive(Default)]
+struct MessageSignal {
+    /// `Some(Some(handle))` - a fresh resumption handle to track; `Some(None)` - the
+    /// previously tracked handle was invalidated; `None` - no change.
+    resume_handle: Option<Option<String>>,
+    /// Gemini sent a `goAway`, warning that it will force-close the connection soon.
+    go_away: bool,
+    /// Tool calls were dispatched and a `toolResponse` is ready to send back upstream.
+    tool_response: Option<ToolResponseMessage>,
+    /// Cumulative token usage just crossed the configured hard budget cap; the caller
+    /// should end the session rather than continue (or reconnect) it.
+    budget_exceeded: bool,
+    /// A configured stop phrase was just spoken in full; the caller should nudge
+    /// Gemini to stop generating the current turn.
+    stop_generation: bool,
 }
 
 impl GeminiLiveClient {
@@ -48,7 +220,11 @@ impl GeminiLiveClient {
         audio_rx: mpsc::Receiver<Vec<u8>>,
         text_rx: mpsc::Receiver<String>,
         event_tx: mpsc::Sender<ClientEvent>,
+        state: AppState,
+        session_token: String,
+        resume_handle: Option<String>,
     ) -> Self {
+        let input_timeline = Arc::new(MediaTimeline::new(config.input_sample_rate));
         Self {
             config,
             setup_config,
@@ -56,6 +232,11 @@ impl GeminiLiveClient {
             text_rx,
             event_tx,
             session_start: Instant::now(),
+            state,
+            session_token,
+            resume_handle,
+            output_timeline: Arc::new(MediaTimeline::new(GEMINI_OUTPUT_SAMPLE_RATE)),
+            input_timeline,
         }
     }
 
@@ -66,72 +247,48 @@ impl GeminiLiveClient {
     /// 2. Sends the setup configuration
     /// 3. Proxies audio/text between the client and Gemini
     /// 4. Forwards responses back to the client
+    ///
+    /// A session/duration-limit close or a `goAway` doesn't end things here: the
+    /// upstream connection is re-established with the latest resumption handle so the
+    /// conversation continues, up to `config.max_reconnect_attempts` times, without
+    /// tearing down the client-facing `event_tx`/audio/text channels.
     pub async fn run(self) -> anyhow::Result<()> {
-        // Build WebSocket URL
         let ws_url = self.config.gemini_ws_url()?;
-        tracing::info!("Connecting to Gemini Live API");
-
-        // Connect to Gemini
-        let (ws_stream, _) = connect_async(&ws_url).await?;
-        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+        let config = self.config.clone();
+        let setup_config = self.setup_config.clone();
+        let max_reconnect_attempts = self.config.max_reconnect_attempts;
 
-        tracing::info!("Connected to Gemini Live API");
-
-        // Send setup message
-        let setup_msg = self.build_setup_message();
-        let setup_json = serde_json::to_string(&setup_msg)?;
-        tracing::debug!("Sending setup to Gemini: {}", setup_json);
-        ws_sender.send(Message::Text(setup_json.into())).await?;
-
-        // Wait for setup complete
-        // Note: Gemini sends JSON as binary WebSocket frames
-        tracing::debug!("Waiting for setup response from Gemini...");
-        if let Some(msg) = ws_receiver.next().await {
-            let text = match msg {
-                Ok(Message::Text(t)) => Some(t.to_string()),
-                Ok(Message::Binary(data)) => String::from_utf8(data.to_vec()).ok(),
-                Ok(Message::Close(reason)) => {
-                    tracing::warn!("Gemini closed connection during setup: {:?}", reason);
-                    return Ok(());
-                }
-                Err(e) => {
-                    tracing::error!("Setup error: {}", e);
-                    return Err(e.into());
-                }
-                _ => None,
-            };
+        // Spawn the audio/text forwarder tasks once. They only depend on the
+        // client-facing audio_rx/text_rx channels, not the Gemini socket, so they
+        // outlive any number of reconnects below untouched.
+        let (gemini_send_tx, mut gemini_send_rx) = mpsc::channel::<String>(100);
 
-            if let Some(text) = text {
-                tracing::debug!("Setup response: {}", text);
-                match serde_json::from_str::<ServerMessage>(&text) {
-                    Ok(response) => {
-                        if response.setup_complete.is_some() {
-                            tracing::info!("Gemini session setup complete");
-                        } else {
-                            tracing::warn!("Setup response did not contain setupComplete");
-                        }
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to parse setup response: {} - raw: {}", e, text);
-                    }
-                }
-            } else {
-                tracing::warn!("Unexpected message type during setup");
-            }
-        } else {
-            tracing::error!("No setup response received from Gemini");
-        }
+        let input_timeline = self.input_timeline;
 
-        // Spawn task to send audio to Gemini
-        let (gemini_send_tx, mut gemini_send_rx) = mpsc::channel::<String>(100);
+        // Build the fallback transcription backend, if configured, before event_tx is
+        // moved into the reconnect loop below. `is_some()` also tells the receive loop
+        // to stop forwarding Gemini's own (unreliable) input transcription, so the
+        // client doesn't see two conflicting caption sources.
+        //
+        // The timestamp provider shares the same input media clock Gemini's own
+        // transcription is stamped with, so a fallback caption lines up with the audio
+        // it describes exactly like a native one would, instead of drifting to whenever
+        // the backend happens to return a result.
+        let transcriber_timeline = input_timeline.clone();
+        let transcriber = build_transcriber(
+            &self.config,
+            self.event_tx.clone(),
+            move || transcriber_timeline.position_ms(),
+        );
+        let use_fallback_transcription = transcriber.is_some();
 
-        // Shared counter for audio chunks sent
         let audio_chunk_count = Arc::new(AtomicU64::new(0));
         let audio_chunk_count_clone = audio_chunk_count.clone();
-
         let audio_sender_tx = gemini_send_tx.clone();
         let sample_rate = self.config.input_sample_rate;
         let mut audio_rx = self.audio_rx;
+        let audio_task_input_timeline = input_timeline.clone();
+        let audio_task_transcriber = transcriber.clone();
 
         tokio::spawn(async move {
             while let Some(audio_data) = audio_rx.recv().await {
@@ -139,6 +296,10 @@ impl GeminiLiveClient {
                 if count % 50 == 1 {
                     tracing::debug!("Received audio chunk #{} from client ({} bytes)", count, audio_data.len());
                 }
+                audio_task_input_timeline.advance(audio_data.len());
+                if let Some(transcriber) = &audio_task_transcriber {
+                    transcriber.push_audio(&audio_data).await;
+                }
                 let msg = RealtimeInputMessage {
                     realtime_input: RealtimeInput::audio_pcm(&audio_data, sample_rate),
                 };
@@ -149,9 +310,16 @@ impl GeminiLiveClient {
             tracing::debug!("Audio sender task ended after {} chunks", audio_chunk_count_clone.load(Ordering::Relaxed));
         });
 
-        // Spawn task to send text to Gemini
+        // Local token estimate for text sent to Gemini, reported alongside Gemini's own
+        // (authoritative but delayed) `usage_metadata` counts so the client/budget check
+        // has a number to work with before a turn round-trips.
+        let tokenizer = Arc::new(std::sync::Mutex::new(Tokenizer::new()?));
+        let estimated_tokens = Arc::new(AtomicU64::new(0));
+
         let text_sender_tx = gemini_send_tx.clone();
         let mut text_rx = self.text_rx;
+        let text_task_tokenizer = tokenizer.clone();
+        let text_task_estimated_tokens = estimated_tokens.clone();
 
         tokio::spawn(async move {
             while let Some(text) = text_rx.recv().await {
@@ -164,6 +332,11 @@ impl GeminiLiveClient {
                     }
                 }
 
+                if let Ok(mut tokenizer) = text_task_tokenizer.lock() {
+                    let count = tokenizer.count(&text);
+                    text_task_estimated_tokens.fetch_add(count as u64, Ordering::Relaxed);
+                }
+
                 // Otherwise, wrap as client content
                 let msg = ClientContentMessage {
                     client_content: ClientContent {
@@ -182,179 +355,478 @@ impl GeminiLiveClient {
                 }
             }
         });
+        drop(gemini_send_tx);
+
+        let event_tx = self.event_tx;
+        let session_start = self.session_start;
+        let state = self.state;
+        let session_token = self.session_token;
+        let output_timeline = self.output_timeline;
+
+        let mut resume_handle = self.resume_handle;
+        let mut total_msg_count = 0u64;
+        let mut last_total_tokens = 0u32;
+        let mut last_prompt_tokens = 0u32;
+        let mut last_response_tokens = 0u32;
+        let mut turn_index = 0u64;
+        let mut attempt = 0u32;
+
+        // The client-supplied setup payload has no dedicated language field, so this
+        // reads an optional `language_pair`/`languagePair` key out of it on a
+        // best-effort basis rather than extending the wire protocol for a column
+        // that's otherwise informational.
+        let language_pair = setup_config
+            .as_ref()
+            .and_then(|v| v.get("language_pair").or_else(|| v.get("languagePair")))
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+
+        if let Err(e) = state
+            .session_store
+            .start_session(&session_token, &config.model, language_pair.as_deref())
+            .await
+        {
+            tracing::warn!("Failed to persist session start: {}", e);
+        }
+
+        crate::metrics::session_started();
+
+        // Count the system instruction once up front so the very first `ClientEvent`
+        // reports a realistic estimate instead of starting from zero.
+        let system_instruction_text = setup_config
+            .as_ref()
+            .and_then(|v| v.get("system_instruction").or_else(|| v.get("systemInstruction")))
+            .and_then(|v| v.get("parts"))
+            .and_then(|v| v.as_array())
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(|part| part.get("text").and_then(|t| t.as_str()))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .unwrap_or_default();
+        if !system_instruction_text.is_empty() {
+            if let Ok(mut tokenizer) = tokenizer.lock() {
+                let count = tokenizer.count(&system_instruction_text);
+                estimated_tokens.fetch_add(count as u64, Ordering::Relaxed);
+            }
+        }
+
+        let tool_registry = ToolRegistry::with_builtin_tools(state.session_store.clone());
+        let embedding_provider = build_embedding_provider(&config);
+
+        loop {
+            let outcome = Self::connect_and_serve(
+                &ws_url,
+                &config,
+                &setup_config,
+                resume_handle.as_deref(),
+                &mut gemini_send_rx,
+                &event_tx,
+                &session_start,
+                &audio_chunk_count,
+                &mut total_msg_count,
+                &mut last_total_tokens,
+                &mut last_prompt_tokens,
+                &mut last_response_tokens,
+                &mut turn_index,
+                &state,
+                &session_token,
+                &output_timeline,
+                &input_timeline,
+                use_fallback_transcription,
+                &tool_registry,
+                &estimated_tokens,
+                &embedding_provider,
+            )
+            .await?;
+
+            match outcome {
+                ConnectionOutcome::Done => break,
+                ConnectionOutcome::Reconnect(new_handle) => {
+                    attempt += 1;
+                    resume_handle = new_handle.or(resume_handle);
+                    crate::metrics::reconnected();
+
+                    if attempt > max_reconnect_attempts {
+                        tracing::warn!(
+                            "Exceeded max reconnect attempts ({}) after a Gemini session limit/goAway, ending session",
+                            max_reconnect_attempts
+                        );
+                        let stats = SessionStats {
+                            message_count: total_msg_count,
+                            audio_chunks_sent: audio_chunk_count.load(Ordering::Relaxed),
+                            elapsed_seconds: session_start.elapsed().as_secs_f64(),
+                            total_token_count: last_total_tokens,
+                            prompt_token_count: last_prompt_tokens,
+                            response_token_count: last_response_tokens,
+                        };
+                        let _ = event_tx.send(ClientEvent::SessionEnd { stats }).await;
+                        break;
+                    }
 
-        // Spawn task to send messages to Gemini WebSocket
-        let send_handle = tokio::spawn(async move {
-            while let Some(msg) = gemini_send_rx.recv().await {
-                if ws_sender.send(Message::Text(msg.into())).await.is_err() {
-                    break;
+                    tracing::info!("Reconnecting to Gemini (attempt {}/{})", attempt, max_reconnect_attempts);
+                    let _ = event_tx
+                        .send(ClientEvent::Reconnecting { attempt, max_attempts: max_reconnect_attempts })
+                        .await;
                 }
             }
-        });
+        }
 
-        // Process responses from Gemini
-        let event_tx = self.event_tx;
-        let session_start = self.session_start;
+        let _ = event_tx.send(ClientEvent::Close).await;
+
+        crate::metrics::session_ended();
+        if let Some(pushgateway_url) = &config.metrics_pushgateway_url {
+            crate::metrics::push(pushgateway_url, &config.metrics_job_name).await;
+        }
+
+        Ok(())
+    }
+
+    /// Connect to Gemini once, run its setup handshake, and proxy messages until the
+    /// connection ends. Returns what `run` should do next.
+    #[allow(clippy::too_many_arguments)]
+    async fn connect_and_serve(
+        ws_url: &str,
+        config: &Config,
+        setup_config: &Option<serde_json::Value>,
+        resume_handle: Option<&str>,
+        gemini_send_rx: &mut mpsc::Receiver<String>,
+        event_tx: &mpsc::Sender<ClientEvent>,
+        session_start: &Instant,
+        audio_chunk_count: &Arc<AtomicU64>,
+        total_msg_count: &mut u64,
+        last_total_tokens: &mut u32,
+        last_prompt_tokens: &mut u32,
+        last_response_tokens: &mut u32,
+        turn_index: &mut u64,
+        state: &AppState,
+        session_token: &str,
+        output_timeline: &MediaTimeline,
+        input_timeline: &MediaTimeline,
+        use_fallback_transcription: bool,
+        tool_registry: &ToolRegistry,
+        estimated_tokens: &Arc<AtomicU64>,
+        embedding_provider: &Option<Arc<dyn EmbeddingProvider>>,
+    ) -> anyhow::Result<ConnectionOutcome> {
+        tracing::info!("Connecting to Gemini Live API");
+        let (ws_stream, _) = connect_async(ws_url).await?;
+        let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+        tracing::info!("Connected to Gemini Live API");
+
+        // Send setup message
+        let setup_msg = Self::build_setup_message(config, setup_config, resume_handle, tool_registry);
+        let setup_json = serde_json::to_string(&setup_msg)?;
+        tracing::debug!("Sending setup to Gemini: {}", setup_json);
+        ws_sender.send(Message::Text(setup_json.into())).await?;
+
+        // Wait for setup complete
+        // Note: Gemini sends JSON as binary WebSocket frames
+        tracing::debug!("Waiting for setup response from Gemini...");
+        if let Some(msg) = ws_receiver.next().await {
+            let text = match msg {
+                Ok(Message::Text(t)) => Some(t.to_string()),
+                Ok(Message::Binary(data)) => String::from_utf8(data.to_vec()).ok(),
+                Ok(Message::Close(reason)) => {
+                    tracing::warn!("Gemini closed connection during setup: {:?}", reason);
+                    return Ok(ConnectionOutcome::Done);
+                }
+                Err(e) => {
+                    tracing::error!("Setup error: {}", e);
+                    return Err(e.into());
+                }
+                _ => None,
+            };
+
+            if let Some(text) = text {
+                tracing::debug!("Setup response: {}", text);
+                match serde_json::from_str::<ServerMessage>(&text) {
+                    Ok(ServerMessage::SetupComplete) => {
+                        // Purely internal bookkeeping: nothing is forwarded to the browser
+                        // client here, so a reconnect re-running this handshake can never
+                        // surface a duplicate "setup complete" to it.
+                        tracing::info!("Gemini session setup complete");
+                    }
+                    Ok(other) => {
+                        tracing::warn!("Setup response did not contain setupComplete: {:?}", other);
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to parse setup response: {} - raw: {}", e, text);
+                    }
+                }
+            } else {
+                tracing::warn!("Unexpected message type during setup");
+            }
+        } else {
+            tracing::error!("No setup response received from Gemini");
+        }
 
         tracing::debug!("Starting main receive loop from Gemini...");
 
         // Keep track of recent messages for debugging policy violations
         let mut recent_messages: std::collections::VecDeque<String> = std::collections::VecDeque::with_capacity(5);
 
-        // Track latest token usage from Gemini (values are cumulative per session)
-        let mut last_total_tokens: u32 = 0;
-        let mut last_prompt_tokens: u32 = 0;
-        let mut last_response_tokens: u32 = 0;
-
         let mut msg_count = 0u64;
-        while let Some(msg) = ws_receiver.next().await {
-            msg_count += 1;
-            match msg {
-                Ok(Message::Text(text)) => {
-                    tracing::debug!("Received text message from Gemini ({} bytes)", text.len());
-                    // Track recent messages for debugging
-                    if recent_messages.len() >= 5 {
-                        recent_messages.pop_front();
-                    }
-                    let preview = truncate_string(&text, 200);
-                    recent_messages.push_back(preview);
+        let mut latest_resume_handle: Option<Option<String>> = None;
+        let mut input_transcript_buf = TranscriptBuffer::default();
+        let mut output_transcript_buf = TranscriptBuffer::default();
+        let mut stop_phrase_matcher = StopPhraseMatcher::new(config.stop_sequences.clone());
 
-                    if let Err(e) = Self::handle_gemini_message(&text, &event_tx, msg_count, &audio_chunk_count, &session_start, &mut last_total_tokens, &mut last_prompt_tokens, &mut last_response_tokens).await {
-                        tracing::error!("Error handling Gemini message: {}", e);
+        loop {
+            tokio::select! {
+                // Drain audio/text the client queued up and forward it to Gemini.
+                // Runs in the same loop as the receive side (rather than a separate
+                // spawned task) so this end of the pipe can be re-spliced to a fresh
+                // socket on reconnect without losing whatever's still in the channel.
+                outgoing = gemini_send_rx.recv() => {
+                    match outgoing {
+                        Some(json) => {
+                            if ws_sender.send(Message::Text(json.into())).await.is_err() {
+                                break;
+                            }
+                        }
+                        None => break, // Client-facing channels closed; nothing left to proxy.
                     }
                 }
-                Ok(Message::Binary(data)) => {
-                    // Gemini sends JSON messages as binary WebSocket frames
-                    // Parse as UTF-8 string and handle as JSON
-                    match String::from_utf8(data.to_vec()) {
-                        Ok(text) => {
-                            tracing::debug!("Received binary JSON from Gemini ({} bytes)", text.len());
-                            // Track recent messages for debugging
+                incoming = ws_receiver.next() => {
+                    let Some(msg) = incoming else { break };
+                    msg_count += 1;
+                    *total_msg_count += 1;
+
+                    match msg {
+                        Ok(Message::Text(text)) => {
+                            tracing::debug!("Received text message from Gemini ({} bytes)", text.len());
                             if recent_messages.len() >= 5 {
                                 recent_messages.pop_front();
                             }
-                            let preview = truncate_string(&text, 200);
-                            recent_messages.push_back(preview);
+                            recent_messages.push_back(truncate_string(&text, 200));
 
-                            if let Err(e) = Self::handle_gemini_message(&text, &event_tx, msg_count, &audio_chunk_count, &session_start, &mut last_total_tokens, &mut last_prompt_tokens, &mut last_response_tokens).await {
-                                tracing::error!("Error handling Gemini message: {}", e);
+                            match Self::handle_gemini_message(&text, event_tx, *total_msg_count, audio_chunk_count, session_start, last_total_tokens, last_prompt_tokens, last_response_tokens, state, session_token, output_timeline, input_timeline, use_fallback_transcription, &config.model, &mut input_transcript_buf, &mut output_transcript_buf, turn_index, tool_registry, estimated_tokens, config.token_budget_soft_limit, config.token_budget_hard_limit, &mut stop_phrase_matcher, embedding_provider, config.embedding_similarity_threshold, config.embedding_cluster_min_size).await {
+                                Ok(signal) => {
+                                    if let Some(update) = signal.resume_handle {
+                                        latest_resume_handle = Some(update);
+                                    }
+                                    if signal.go_away {
+                                        tracing::info!("Reconnecting proactively ahead of Gemini's goAway");
+                                        return Ok(ConnectionOutcome::Reconnect(latest_resume_handle.flatten()));
+                                    }
+                                    if signal.budget_exceeded {
+                                        let stats = SessionStats {
+                                            message_count: *total_msg_count,
+                                            audio_chunks_sent: audio_chunk_count.load(Ordering::Relaxed),
+                                            elapsed_seconds: session_start.elapsed().as_secs_f64(),
+                                            total_token_count: *last_total_tokens,
+                                            prompt_token_count: *last_prompt_tokens,
+                                            response_token_count: *last_response_tokens,
+                                        };
+                                        let _ = event_tx.send(ClientEvent::SessionEnd { stats }).await;
+                                        return Ok(ConnectionOutcome::Done);
+                                    }
+                                    if let Some(tool_response) = signal.tool_response {
+                                        if let Ok(json) = serde_json::to_string(&tool_response) {
+                                            if ws_sender.send(Message::Text(json.into())).await.is_err() {
+                                                break;
+                                            }
+                                        }
+                                    }
+                                    if signal.stop_generation {
+                                        tracing::info!("Stop phrase matched, halting model turn");
+                                        if let Ok(json) = serde_json::to_string(&Self::stop_generation_message()) {
+                                            if ws_sender.send(Message::Text(json.into())).await.is_err() {
+                                                break;
+                                            }
+                                        }
+                                    }
+                                }
+                                Err(e) => tracing::error!("Error handling Gemini message: {}", e),
                             }
                         }
-                        Err(_) => {
-                            // Truly binary data (unlikely, but handle it)
-                            tracing::warn!("Received non-UTF8 binary from Gemini: {} bytes", data.len());
-                            let mut audio_data = data.to_vec();
-                            if audio_data.len() % 2 != 0 {
-                                audio_data.push(0);
+                        Ok(Message::Binary(data)) => {
+                            // Gemini sends JSON messages as binary WebSocket frames
+                            match String::from_utf8(data.to_vec()) {
+                                Ok(text) => {
+                                    tracing::debug!("Received binary JSON from Gemini ({} bytes)", text.len());
+                                    if recent_messages.len() >= 5 {
+                                        recent_messages.pop_front();
+                                    }
+                                    recent_messages.push_back(truncate_string(&text, 200));
+
+                                    match Self::handle_gemini_message(&text, event_tx, *total_msg_count, audio_chunk_count, session_start, last_total_tokens, last_prompt_tokens, last_response_tokens, state, session_token, output_timeline, input_timeline, use_fallback_transcription, &config.model, &mut input_transcript_buf, &mut output_transcript_buf, turn_index, tool_registry, estimated_tokens, config.token_budget_soft_limit, config.token_budget_hard_limit, &mut stop_phrase_matcher, embedding_provider, config.embedding_similarity_threshold, config.embedding_cluster_min_size).await {
+                                        Ok(signal) => {
+                                            if let Some(update) = signal.resume_handle {
+                                                latest_resume_handle = Some(update);
+                                            }
+                                            if signal.go_away {
+                                                tracing::info!("Reconnecting proactively ahead of Gemini's goAway");
+                                                return Ok(ConnectionOutcome::Reconnect(latest_resume_handle.flatten()));
+                                            }
+                                            if signal.budget_exceeded {
+                                                let stats = SessionStats {
+                                                    message_count: *total_msg_count,
+                                                    audio_chunks_sent: audio_chunk_count.load(Ordering::Relaxed),
+                                                    elapsed_seconds: session_start.elapsed().as_secs_f64(),
+                                                    total_token_count: *last_total_tokens,
+                                                    prompt_token_count: *last_prompt_tokens,
+                                                    response_token_count: *last_response_tokens,
+                                                };
+                                                let _ = event_tx.send(ClientEvent::SessionEnd { stats }).await;
+                                                return Ok(ConnectionOutcome::Done);
+                                            }
+                                            if let Some(tool_response) = signal.tool_response {
+                                                if let Ok(json) = serde_json::to_string(&tool_response) {
+                                                    if ws_sender.send(Message::Text(json.into())).await.is_err() {
+                                                        break;
+                                                    }
+                                                }
+                                            }
+                                            if signal.stop_generation {
+                                                tracing::info!("Stop phrase matched, halting model turn");
+                                                if let Ok(json) = serde_json::to_string(&Self::stop_generation_message()) {
+                                                    if ws_sender.send(Message::Text(json.into())).await.is_err() {
+                                                        break;
+                                                    }
+                                                }
+                                            }
+                                        }
+                                        Err(e) => tracing::error!("Error handling Gemini message: {}", e),
+                                    }
+                                }
+                                Err(_) => {
+                                    // Truly binary data (unlikely, but handle it)
+                                    tracing::warn!("Received non-UTF8 binary from Gemini: {} bytes", data.len());
+                                    let mut audio_data = data.to_vec();
+                                    if audio_data.len() % 2 != 0 {
+                                        audio_data.push(0);
+                                    }
+                                    let (timestamp_ms, duration_ms) = output_timeline.advance(audio_data.len());
+                                    crate::metrics::audio_bytes_forwarded(audio_data.len());
+                                    let _ = event_tx
+                                        .send(ClientEvent::Audio { data: audio_data, timestamp_ms, duration_ms })
+                                        .await;
+                                }
                             }
-                            let _ = event_tx.send(ClientEvent::Audio(audio_data)).await;
                         }
-                    }
-                }
-                Ok(Message::Close(reason)) => {
-                    let audio_chunks = audio_chunk_count.load(Ordering::Relaxed);
-                    let elapsed = session_start.elapsed().as_secs_f64();
-                    let stats = SessionStats {
-                        message_count: msg_count,
-                        audio_chunks_sent: audio_chunks,
-                        elapsed_seconds: elapsed,
-                        total_token_count: last_total_tokens,
-                        prompt_token_count: last_prompt_tokens,
-                        response_token_count: last_response_tokens,
-                    };
-
-                    if let Some(ref frame) = reason {
-                        tracing::warn!(
-                            "Gemini closed - Code: {:?}, Reason: {} (after {} messages, {} audio chunks, {:.1}s elapsed, {} tokens)",
-                            frame.code,
-                            frame.reason,
-                            msg_count,
-                            audio_chunks,
-                            elapsed,
-                            last_total_tokens
-                        );
+                        Ok(Message::Close(reason)) => {
+                            let audio_chunks = audio_chunk_count.load(Ordering::Relaxed);
+                            let elapsed = session_start.elapsed().as_secs_f64();
+                            let stats = SessionStats {
+                                message_count: *total_msg_count,
+                                audio_chunks_sent: audio_chunks,
+                                elapsed_seconds: elapsed,
+                                total_token_count: *last_total_tokens,
+                                prompt_token_count: *last_prompt_tokens,
+                                response_token_count: *last_response_tokens,
+                            };
 
-                        // Determine if this is likely a session limit vs early policy error
-                        let is_likely_session_limit = msg_count > 100; // If we got >100 messages, features work
-
-                        if frame.reason.contains("Internal error") {
-                            tracing::warn!(
-                                "Gemini internal error after {} messages - may be caused by tool response issues or transient API failure",
-                                msg_count
-                            );
-                            let error_message = "Session ended unexpectedly: Gemini encountered an internal error. Please try again.".to_string();
-                            let _ = event_tx.send(ClientEvent::Error { message: error_message, stats: Some(stats) }).await;
-                        } else if frame.reason.contains("Policy") || frame.reason.contains("not implemented") || frame.reason.contains("not supported") || frame.reason.contains("not enabled") {
-                            if is_likely_session_limit {
-                                // Session ran for a while - this is likely a context/duration limit
-                                tracing::info!(
-                                    "Session ended after {} messages, {} audio chunks - likely hit Gemini's session or context limit",
+                            if let Some(ref frame) = reason {
+                                tracing::warn!(
+                                    "Gemini closed - Code: {:?}, Reason: {} (after {} messages this connection, {} audio chunks, {:.1}s elapsed, {} tokens)",
+                                    frame.code,
+                                    frame.reason,
                                     msg_count,
-                                    audio_chunks
+                                    audio_chunks,
+                                    elapsed,
+                                    last_total_tokens
                                 );
-                                let error_message = "Session ended: Gemini's session limit reached. Please start a new conversation.".to_string();
-                                let _ = event_tx.send(ClientEvent::Error { message: error_message, stats: Some(stats) }).await;
-                            } else {
-                                // Early termination - likely a real feature incompatibility
-                                tracing::error!(
-                                    "POLICY VIOLATION DETECTED - Check: model support for Live API, incompatible feature combinations (e.g., transcription + tools), API key restrictions, or region limitations"
-                                );
-                                // Log recent messages for debugging
-                                tracing::error!("Last {} messages before policy violation:", recent_messages.len());
-                                for (i, msg) in recent_messages.iter().enumerate() {
-                                    tracing::error!("  [{}]: {}", i + 1, msg);
+
+                                // Determine if this is likely a session limit vs early policy error
+                                let is_likely_session_limit = msg_count > 100; // If we got >100 messages, features work
+
+                                if frame.reason.contains("Internal error") {
+                                    tracing::warn!(
+                                        "Gemini internal error after {} messages - may be caused by tool response issues or transient API failure",
+                                        msg_count
+                                    );
+                                    let error_message = "Session ended unexpectedly: Gemini encountered an internal error. Please try again.".to_string();
+                                    let _ = event_tx.send(ClientEvent::Error { message: error_message, stats: Some(stats) }).await;
+                                    return Ok(ConnectionOutcome::Done);
+                                } else if frame.reason.contains("Policy") || frame.reason.contains("not implemented") || frame.reason.contains("not supported") || frame.reason.contains("not enabled") {
+                                    if is_likely_session_limit {
+                                        // Session ran for a while - this is likely a context/duration
+                                        // limit, which is resumable, so reconnect instead of ending.
+                                        tracing::info!(
+                                            "Session ended after {} messages, {} audio chunks - likely hit Gemini's session or context limit, reconnecting",
+                                            msg_count,
+                                            audio_chunks
+                                        );
+                                        return Ok(ConnectionOutcome::Reconnect(latest_resume_handle.flatten()));
+                                    } else {
+                                        // Early termination - likely a real feature incompatibility
+                                        tracing::error!(
+                                            "POLICY VIOLATION DETECTED - Check: model support for Live API, incompatible feature combinations (e.g., transcription + tools), API key restrictions, or region limitations"
+                                        );
+                                        tracing::error!("Last {} messages before policy violation:", recent_messages.len());
+                                        for (i, msg) in recent_messages.iter().enumerate() {
+                                            tracing::error!("  [{}]: {}", i + 1, msg);
+                                        }
+                                        let error_message = format!(
+                                            "Session ended: {}. This may be due to an unsupported feature combination or API limitation.",
+                                            frame.reason
+                                        );
+                                        crate::metrics::policy_closed();
+                                        let _ = event_tx.send(ClientEvent::Error { message: error_message, stats: Some(stats) }).await;
+                                        return Ok(ConnectionOutcome::Done);
+                                    }
+                                } else {
+                                    // Normal close with a reason
+                                    let _ = event_tx.send(ClientEvent::SessionEnd { stats }).await;
+                                    return Ok(ConnectionOutcome::Done);
                                 }
-                                // Send error to client before closing
-                                let error_message = format!(
-                                    "Session ended: {}. This may be due to an unsupported feature combination or API limitation.",
-                                    frame.reason
-                                );
-                                let _ = event_tx.send(ClientEvent::Error { message: error_message, stats: Some(stats) }).await;
+                            } else {
+                                tracing::info!("Gemini closed connection (no reason provided) after {} messages, {} audio chunks", msg_count, audio_chunks);
+                                let _ = event_tx.send(ClientEvent::SessionEnd { stats }).await;
+                                return Ok(ConnectionOutcome::Done);
                             }
-                        } else {
-                            // Normal close with a reason
-                            let _ = event_tx.send(ClientEvent::SessionEnd { stats }).await;
                         }
-                    } else {
-                        tracing::info!("Gemini closed connection (no reason provided) after {} messages, {} audio chunks", msg_count, audio_chunks);
-                        let _ = event_tx.send(ClientEvent::SessionEnd { stats }).await;
+                        Ok(Message::Ping(_)) | Ok(Message::Pong(_)) => {}
+                        Err(e) => {
+                            let audio_chunks = audio_chunk_count.load(Ordering::Relaxed);
+                            let elapsed = session_start.elapsed().as_secs_f64();
+                            tracing::error!("Gemini WebSocket error: {} (after {} messages, {} audio chunks, {:.1}s elapsed, {} tokens)", e, msg_count, audio_chunks, elapsed, last_total_tokens);
+                            let stats = SessionStats {
+                                message_count: *total_msg_count,
+                                audio_chunks_sent: audio_chunks,
+                                elapsed_seconds: elapsed,
+                                total_token_count: *last_total_tokens,
+                                prompt_token_count: *last_prompt_tokens,
+                                response_token_count: *last_response_tokens,
+                            };
+                            let _ = event_tx.send(ClientEvent::Error {
+                                message: format!("Connection error: {}", e),
+                                stats: Some(stats),
+                            }).await;
+                            return Ok(ConnectionOutcome::Done);
+                        }
+                        _ => {}
                     }
-                    break;
                 }
-                Ok(Message::Ping(_)) | Ok(Message::Pong(_)) => {}
-                Err(e) => {
-                    let audio_chunks = audio_chunk_count.load(Ordering::Relaxed);
-                    let elapsed = session_start.elapsed().as_secs_f64();
-                    tracing::error!("Gemini WebSocket error: {} (after {} messages, {} audio chunks, {:.1}s elapsed, {} tokens)", e, msg_count, audio_chunks, elapsed, last_total_tokens);
-                    let stats = SessionStats {
-                        message_count: msg_count,
-                        audio_chunks_sent: audio_chunks,
-                        elapsed_seconds: elapsed,
-                        total_token_count: last_total_tokens,
-                        prompt_token_count: last_prompt_tokens,
-                        response_token_count: last_response_tokens,
-                    };
-                    let _ = event_tx.send(ClientEvent::Error {
-                        message: format!("Connection error: {}", e),
-                        stats: Some(stats),
-                    }).await;
-                    break;
-                }
-                _ => {}
             }
         }
-        tracing::debug!("Gemini receive loop ended after {} messages", msg_count);
 
-        // Send close signal (SessionEnd/Error already sent from handlers above)
-        let _ = event_tx.send(ClientEvent::Close).await;
-        send_handle.abort();
+        tracing::debug!("Gemini receive loop ended after {} messages this connection", msg_count);
+        Ok(ConnectionOutcome::Done)
+    }
 
-        Ok(())
+    /// Best-effort nudge to stop the model's current turn once a stop phrase is
+    /// matched. The Live API doesn't expose an explicit "cancel generation" op, so
+    /// this reuses the same mechanism that already interrupts a turn in practice:
+    /// sending a (empty, turn-complete) client turn, the same way real user speech
+    /// naturally barges in on whatever the model was saying.
+    fn stop_generation_message() -> ClientContentMessage {
+        ClientContentMessage {
+            client_content: ClientContent { turns: vec![], turn_complete: true },
+        }
     }
 
     /// Build the setup message from client config and defaults.
-    fn build_setup_message(&self) -> SetupMessage {
-        let model = format!("models/{}", self.config.model);
+    fn build_setup_message(
+        config: &Config,
+        setup_config: &Option<serde_json::Value>,
+        resume_handle: Option<&str>,
+        tool_registry: &ToolRegistry,
+    ) -> SetupMessage {
+        let model = format!("models/{}", config.model);
 
         // Start with defaults
         let mut generation_config = GenerationConfig {
@@ -366,7 +838,7 @@ impl GeminiLiveClient {
         let mut tools: Option<Vec<Tool>> = None;
 
         // Override with client config if provided
-        if let Some(setup) = &self.setup_config {
+        if let Some(setup) = setup_config {
             // Parse generation config
             if let Some(gen) = setup.get("generation_config") {
                 if let Some(modalities) = gen.get("response_modalities") {
@@ -396,6 +868,16 @@ impl GeminiLiveClient {
             }
         }
 
+        // Always advertise the server-side tool registry's functions, in addition to
+        // whatever the client requested, so Gemini knows it can call e.g. `lookup_word`
+        // even if the browser's own setup message never mentioned tools.
+        let registry_declarations = tool_registry.function_declarations();
+        if !registry_declarations.is_empty() {
+            tools.get_or_insert_with(Vec::new).push(Tool {
+                function_declarations: Some(registry_declarations),
+            });
+        }
+
         // Enable context window compression to extend sessions beyond the default limit.
         // Without this, sessions hit the 128K context window and Gemini closes with a Policy error.
         let context_window_compression = Some(ContextWindowCompression {
@@ -405,11 +887,18 @@ impl GeminiLiveClient {
             },
         });
 
+        // Always opt in to session resumption; replay a stored handle if we have one
+        // from a previous connection so Gemini continues the same conversation.
+        let session_resumption = Some(SessionResumptionConfig {
+            handle: resume_handle.map(|h| h.to_string()),
+        });
+
         tracing::info!(
-            "Setup config - Model: {}, Has tools: {}, Has system_instruction: {}, Context compression: enabled (trigger: 100K, target: 50K)",
+            "Setup config - Model: {}, Has tools: {}, Has system_instruction: {}, Context compression: enabled (trigger: 100K, target: 50K), Resuming: {}",
             model,
             tools.is_some(),
-            system_instruction.is_some()
+            system_instruction.is_some(),
+            resume_handle.is_some()
         );
 
         SetupMessage {
@@ -419,11 +908,15 @@ impl GeminiLiveClient {
                 system_instruction,
                 tools,
                 context_window_compression,
+                session_resumption,
             },
         }
     }
 
-    /// Handle a message from Gemini and forward relevant events to the client.
+    /// Handle a message from Gemini, forward relevant events to the client, and
+    /// report any change to session continuity (a new/invalidated resumption handle,
+    /// or a `goAway`) for the caller to act on.
+    #[allow(clippy::too_many_arguments)]
     async fn handle_gemini_message(
         text: &str,
         event_tx: &mpsc::Sender<ClientEvent>,
@@ -433,7 +926,24 @@ impl GeminiLiveClient {
         last_total_tokens: &mut u32,
         last_prompt_tokens: &mut u32,
         last_response_tokens: &mut u32,
-    ) -> anyhow::Result<()> {
+        state: &AppState,
+        session_token: &str,
+        output_timeline: &MediaTimeline,
+        input_timeline: &MediaTimeline,
+        use_fallback_transcription: bool,
+        model: &str,
+        input_transcript_buf: &mut TranscriptBuffer,
+        output_transcript_buf: &mut TranscriptBuffer,
+        turn_index: &mut u64,
+        tool_registry: &ToolRegistry,
+        estimated_tokens: &Arc<AtomicU64>,
+        token_budget_soft_limit: Option<u32>,
+        token_budget_hard_limit: Option<u32>,
+        stop_phrase_matcher: &mut StopPhraseMatcher,
+        embedding_provider: &Option<Arc<dyn EmbeddingProvider>>,
+        embedding_similarity_threshold: f32,
+        embedding_cluster_min_size: usize,
+    ) -> anyhow::Result<MessageSignal> {
         // Log raw message for debugging (truncate if too long)
         let preview = truncate_string(text, 500);
         tracing::debug!("Raw Gemini message: {}", preview);
@@ -447,16 +957,29 @@ impl GeminiLiveClient {
         }
 
         let msg: ServerMessage = serde_json::from_str(text)?;
+        tracing::debug!("Parsed message variant: {:?}", msg);
 
-        tracing::debug!(
-            "Parsed message - setup_complete: {:?}, server_content: {:?}, tool_call: {:?}, usage_metadata: {:?}",
-            msg.setup_complete.is_some(),
-            msg.server_content.is_some(),
-            msg.tool_call.is_some(),
-            msg.usage_metadata.is_some()
-        );
+        let (server_content, tool_call, tool_call_cancellation, usage_metadata, session_resumption_update, go_away) =
+            match msg {
+                ServerMessage::SetupComplete => (None, None, None, None, None, None),
+                ServerMessage::ServerContent(sc) => (Some(sc), None, None, None, None, None),
+                ServerMessage::ToolCall(tc) => (None, Some(tc), None, None, None, None),
+                ServerMessage::ToolCallCancellation(tcc) => (None, None, Some(tcc), None, None, None),
+                ServerMessage::UsageMetadata(um) => (None, None, None, Some(um), None, None),
+                ServerMessage::SessionResumptionUpdate(sru) => (None, None, None, None, Some(sru), None),
+                ServerMessage::GoAway(ga) => (None, None, None, None, None, Some(ga)),
+                ServerMessage::Unknown(raw) => {
+                    tracing::warn!("Received unrecognized Gemini server message, ignoring: {}", raw);
+                    (None, None, None, None, None, None)
+                }
+            };
+
+        if let Some(cancellation) = tool_call_cancellation {
+            tracing::info!("Gemini requested tool call cancellation for ids: {:?}", cancellation.ids);
+        }
 
-        if let Some(server_content) = msg.server_content {
+        let mut stop_generation = false;
+        if let Some(server_content) = server_content {
             tracing::debug!(
                 "ServerContent - model_turn: {:?}, input_transcription: {:?}, output_transcription: {:?}, turn_complete: {:?}, interrupted: {:?}, generation_complete: {:?}",
                 server_content.model_turn.is_some(),
@@ -495,7 +1018,11 @@ impl GeminiLiveClient {
                             if audio_data.len() % 2 != 0 {
                                 audio_data.push(0);
                             }
-                            event_tx.send(ClientEvent::Audio(audio_data)).await?;
+                            let (timestamp_ms, duration_ms) = output_timeline.advance(audio_data.len());
+                            crate::metrics::audio_bytes_forwarded(audio_data.len());
+                            event_tx
+                                .send(ClientEvent::Audio { data: audio_data, timestamp_ms, duration_ms })
+                                .await?;
                         }
                     }
                 }
@@ -511,20 +1038,161 @@ impl GeminiLiveClient {
 
             let mut has_event = false;
 
+            // Gemini's own input transcription is skipped entirely when a fallback
+            // backend is active, so the client only ever sees one source of captions.
+            // Fragments accumulate in a per-turn buffer and go out as partials
+            // (`finished: false`) as they stream in; the buffer is only flushed as
+            // final once the turn actually ends, below.
             if let Some(input) = server_content.input_transcription {
-                client_content.input_transcription = Some(ClientTranscription {
-                    text: input.text,
-                    finished: true,
-                });
-                has_event = true;
+                if !use_fallback_transcription {
+                    input_transcript_buf.push(&input.text);
+                    client_content.input_transcription = Some(ClientTranscription {
+                        text: input_transcript_buf.text.clone(),
+                        finished: false,
+                        timestamp_ms: input_timeline.position_ms(),
+                    });
+                    has_event = true;
+                }
             }
 
+            // Each fragment is checked against the configured stop phrases before it's
+            // added to the transcript buffer: a fragment that's still a strict prefix of
+            // a stop phrase is held rather than shown, so a learner-configured phrase
+            // like "let's move on" doesn't have to fully stream to the client before it
+            // can be matched, and a match halts the turn instead of being narrated.
+            let mut stop_phrase_matched = false;
             if let Some(output) = server_content.output_transcription {
-                client_content.output_transcription = Some(ClientTranscription {
-                    text: output.text,
-                    finished: true,
-                });
-                has_event = true;
+                match stop_phrase_matcher.push(&output.text) {
+                    StopPhraseSignal::Flush(text) => {
+                        output_transcript_buf.push(&text);
+                        client_content.output_transcription = Some(ClientTranscription {
+                            text: output_transcript_buf.text.clone(),
+                            finished: false,
+                            timestamp_ms: output_timeline.position_ms(),
+                        });
+                        has_event = true;
+                    }
+                    StopPhraseSignal::Holding => {}
+                    StopPhraseSignal::Matched => {
+                        stop_phrase_matched = true;
+                    }
+                }
+            }
+
+            // A turn ending is the only point a transcript is actually final: flush
+            // whatever each buffer has accumulated, reset it for the next turn, and
+            // persist each side that said something as its own turn row so the
+            // conversation can be replayed later.
+            let turn_finished =
+                server_content.turn_complete == Some(true) || server_content.generation_complete == Some(true);
+            if turn_finished {
+                *turn_index += 1;
+                let interrupted = server_content.interrupted == Some(true);
+
+                if !input_transcript_buf.is_empty() {
+                    let text = input_transcript_buf.take();
+                    client_content.input_transcription = Some(ClientTranscription {
+                        text: text.clone(),
+                        finished: true,
+                        timestamp_ms: input_timeline.position_ms(),
+                    });
+                    has_event = true;
+                    let turn = crate::session_store::TurnRecord {
+                        session_id: session_token.to_string(),
+                        turn_index: *turn_index,
+                        role: "user",
+                        text: text.clone(),
+                        audio_chunk_count: audio_chunk_count.load(Ordering::Relaxed),
+                        prompt_tokens: *last_prompt_tokens,
+                        response_tokens: *last_response_tokens,
+                        total_tokens: *last_total_tokens,
+                        interrupted,
+                    };
+                    if let Err(e) = state.session_store.finalize_turn(turn).await {
+                        tracing::warn!("Failed to persist input turn: {}", e);
+                    }
+
+                    // There's no grammar-mistake classifier in this codebase to tell a
+                    // flubbed utterance from an ordinary one, so every finalized learner
+                    // utterance is treated as a candidate and recurrence itself - the same
+                    // thing said several times within this session - is taken as the signal
+                    // that it's worth surfacing for review. Matching is scoped to this
+                    // session_id (see `find_similar_errors`) since there's no stable learner
+                    // identity to match on across sessions.
+                    if let Some(provider) = embedding_provider {
+                        match provider.embed(&text).await {
+                            Ok(embedding) => {
+                                let saved_id = match state
+                                    .session_store
+                                    .save_error_embedding(session_token, *turn_index, &text, &embedding)
+                                    .await
+                                {
+                                    Ok(id) => Some(id),
+                                    Err(e) => {
+                                        tracing::warn!("Failed to persist error embedding: {}", e);
+                                        None
+                                    }
+                                };
+                                let exclude_id = saved_id.unwrap_or(-1);
+                                match state
+                                    .session_store
+                                    .find_similar_errors(session_token, embedding, 20, exclude_id)
+                                    .await
+                                {
+                                    Ok(matches) => {
+                                        let similar: Vec<_> = matches
+                                            .into_iter()
+                                            .filter(|m| m.similarity >= embedding_similarity_threshold)
+                                            .collect();
+                                        if similar.len() + 1 >= embedding_cluster_min_size {
+                                            let review_msg = ClientEventMessage {
+                                                server_content: None,
+                                                tool_call: None,
+                                                usage_metadata: None,
+                                                session_stats: None,
+                                                token_budget_warning: None,
+                                                review_suggestion: Some(ClientReviewSuggestion {
+                                                    pattern_text: text.clone(),
+                                                    example_texts: similar.iter().map(|m| m.text.clone()).collect(),
+                                                    occurrence_count: similar.len() + 1,
+                                                }),
+                                                error: None,
+                                            };
+                                            if let Ok(json) = serde_json::to_string(&review_msg) {
+                                                let _ = event_tx.send(ClientEvent::Json(json)).await;
+                                            }
+                                        }
+                                    }
+                                    Err(e) => tracing::warn!("Failed to query similar errors: {}", e),
+                                }
+                            }
+                            Err(e) => tracing::warn!("Failed to embed learner utterance: {}", e),
+                        }
+                    }
+                }
+                if !output_transcript_buf.is_empty() {
+                    let text = output_transcript_buf.take();
+                    client_content.output_transcription = Some(ClientTranscription {
+                        text: text.clone(),
+                        finished: true,
+                        timestamp_ms: output_timeline.position_ms(),
+                    });
+                    has_event = true;
+                    let turn = crate::session_store::TurnRecord {
+                        session_id: session_token.to_string(),
+                        turn_index: *turn_index,
+                        role: "model",
+                        text,
+                        audio_chunk_count: audio_chunk_count.load(Ordering::Relaxed),
+                        prompt_tokens: *last_prompt_tokens,
+                        response_tokens: *last_response_tokens,
+                        total_tokens: *last_total_tokens,
+                        interrupted,
+                    };
+                    if let Err(e) = state.session_store.finalize_turn(turn).await {
+                        tracing::warn!("Failed to persist output turn: {}", e);
+                    }
+                }
             }
 
             if server_content.turn_complete == Some(true) {
@@ -532,9 +1200,16 @@ impl GeminiLiveClient {
                 has_event = true;
             }
 
-            if server_content.interrupted == Some(true) {
+            if server_content.interrupted == Some(true) || stop_phrase_matched {
                 client_content.interrupted = Some(true);
                 has_event = true;
+                stop_generation = stop_phrase_matched;
+                // Either Gemini discarded buffered output audio for this turn (barge-in),
+                // or a configured stop phrase was just spoken in full; either way reset the
+                // output clock so post-interrupt timestamps don't drift from the cut-off,
+                // and drop the now-stale partial output transcript with it.
+                output_timeline.reset();
+                output_transcript_buf.take();
             }
 
             if has_event {
@@ -543,36 +1218,54 @@ impl GeminiLiveClient {
                     tool_call: None,
                     usage_metadata: None,
                     session_stats: None,
+                    token_budget_warning: None,
+                    review_suggestion: None,
+                    error: None,
                 };
                 let json = serde_json::to_string(&event_msg)?;
                 event_tx.send(ClientEvent::Json(json)).await?;
             }
         }
 
-        // Handle tool calls
-        if let Some(tool_call) = msg.tool_call {
+        // Handle tool calls: notify the client which functions were requested, then run
+        // each through the registry and forward the result back as the caller's return
+        // value, so `connect_and_serve` can send the `toolResponse` Gemini is waiting on.
+        let mut tool_response: Option<ToolResponseMessage> = None;
+        let mut budget_exceeded = false;
+        if let Some(tool_call) = tool_call {
             let event_msg = ClientEventMessage {
                 server_content: None,
                 tool_call: Some(ClientToolCall {
-                    function_calls: tool_call.function_calls.into_iter().map(|fc| fc.into()).collect(),
+                    function_calls: tool_call.function_calls.iter().cloned().map(Into::into).collect(),
                 }),
                 usage_metadata: None,
                 session_stats: Some(ClientSessionStats {
                     message_count: msg_count,
                     audio_chunks_sent: audio_chunk_count.load(Ordering::Relaxed),
                     elapsed_seconds: session_start.elapsed().as_secs_f64(),
+                    estimated_token_count: estimated_tokens.load(Ordering::Relaxed) as u32,
+                    actual_token_count: *last_total_tokens,
                 }),
+                token_budget_warning: None,
+                review_suggestion: None,
+                error: None,
             };
             let json = serde_json::to_string(&event_msg)?;
             event_tx.send(ClientEvent::Json(json)).await?;
+
+            tool_response = Some(tool_registry.dispatch(session_token, tool_call.function_calls).await);
         }
 
         // Handle usage metadata
-        if let Some(usage) = msg.usage_metadata {
+        if let Some(usage) = usage_metadata {
             let prompt = usage.prompt_token_count.unwrap_or(0);
             let response = usage.response_token_count.unwrap_or(0);
             let total = usage.total_token_count.unwrap_or(0);
 
+            // Gemini reports cumulative totals for the session, not deltas, so diff
+            // against the last reading before overwriting it for the counter.
+            crate::metrics::tokens_consumed(model, total.saturating_sub(*last_total_tokens));
+
             // Update latest token counts
             *last_total_tokens = total;
             *last_prompt_tokens = prompt;
@@ -590,13 +1283,83 @@ impl GeminiLiveClient {
                     prompt_token_count: prompt,
                     response_token_count: response,
                     total_token_count: total,
+                    estimated_token_count: estimated_tokens.load(Ordering::Relaxed) as u32,
                 }),
                 session_stats: None,
+                token_budget_warning: None,
+                review_suggestion: None,
+                error: None,
             };
             let json = serde_json::to_string(&event_msg)?;
             event_tx.send(ClientEvent::Json(json)).await?;
+
+            // A hard cap takes priority over a soft warning: there's no point warning
+            // about a budget the session is about to be closed for exceeding anyway.
+            if let Some(hard_limit) = token_budget_hard_limit {
+                if total >= hard_limit {
+                    tracing::warn!("Session hit hard token budget ({} >= {}), ending session", total, hard_limit);
+                    budget_exceeded = true;
+                }
+            }
+            if !budget_exceeded {
+                if let Some(soft_limit) = token_budget_soft_limit {
+                    if total >= soft_limit {
+                        tracing::warn!("Session crossed soft token budget ({} >= {})", total, soft_limit);
+                        let warning_msg = ClientEventMessage {
+                            server_content: None,
+                            tool_call: None,
+                            usage_metadata: None,
+                            session_stats: None,
+                            token_budget_warning: Some(ClientTokenBudgetWarning {
+                                total_token_count: total,
+                                soft_limit,
+                            }),
+                            review_suggestion: None,
+                            error: None,
+                        };
+                        let json = serde_json::to_string(&warning_msg)?;
+                        event_tx.send(ClientEvent::Json(json)).await?;
+                    }
+                }
+            }
         }
 
-        Ok(())
+        let mut signal = MessageSignal {
+            tool_response,
+            budget_exceeded,
+            stop_generation,
+            ..Default::default()
+        };
+
+        // Capture session-resumption handles so a dropped connection can continue
+        // the same conversation, whether via our own reconnect below or a later
+        // `resume_token` on a fresh `/api/auth` call.
+        if let Some(update) = session_resumption_update {
+            match (update.new_handle, update.resumable) {
+                (Some(handle), Some(false)) => {
+                    tracing::warn!("Gemini reported resumption handle as no longer resumable");
+                    state.invalidate_resume_handle(session_token).await;
+                    signal.resume_handle = Some(None);
+                    let _ = handle; // superseded, nothing to forward
+                }
+                (Some(handle), _) => {
+                    tracing::debug!("Received new session-resumption handle");
+                    state.store_resume_handle(session_token, handle.clone()).await;
+                    event_tx.send(ClientEvent::ResumeHandle(handle.clone())).await?;
+                    signal.resume_handle = Some(Some(handle));
+                }
+                (None, _) => {}
+            }
+        }
+
+        // Gemini warns before it force-closes the connection; surface it so the
+        // client can prepare to reconnect with the resumption handle we've saved.
+        if let Some(go_away) = go_away {
+            tracing::info!("Gemini sent goAway, time_left: {:?}", go_away.time_left);
+            event_tx.send(ClientEvent::GoAway { time_left: go_away.time_left }).await?;
+            signal.go_away = true;
+        }
+
+        Ok(signal)
     }
 }
@@ -0,0 +1,79 @@
+//! Pluggable embedding backend for turning learner utterances into vectors, so
+//! recurring mistakes can be recalled by semantic similarity instead of exact text
+//! match later. Mirrors `transcription::InputTranscriber`'s pluggable-backend shape:
+//! a small trait, a `"none"`-by-default factory, and one real implementation.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::config::Config;
+
+/// Turns a piece of text into a fixed-size embedding vector.
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>>;
+}
+
+/// Build the configured `EmbeddingProvider` for a new session, if any.
+///
+/// `"none"` (the default) returns `None`, disabling error-memory recall entirely.
+/// Unrecognized values also disable it rather than failing the connection.
+pub fn build_embedding_provider(config: &Config) -> Option<Arc<dyn EmbeddingProvider>> {
+    match config.embedding_backend.as_str() {
+        "gemini" => config.api_key.clone().map(|api_key| {
+            Arc::new(GeminiEmbeddingProvider {
+                api_key,
+                model: "text-embedding-004".to_string(),
+            }) as Arc<dyn EmbeddingProvider>
+        }),
+        "none" | "" => None,
+        other => {
+            tracing::warn!("Unknown EMBEDDING_BACKEND '{}', disabling error-memory recall", other);
+            None
+        }
+    }
+}
+
+/// Embeddings backed by Gemini's `embedContent` REST endpoint.
+struct GeminiEmbeddingProvider {
+    api_key: String,
+    model: String,
+}
+
+#[async_trait]
+impl EmbeddingProvider for GeminiEmbeddingProvider {
+    async fn embed(&self, text: &str) -> anyhow::Result<Vec<f32>> {
+        let url = format!(
+            "https://generativelanguage.googleapis.com/v1beta/models/{}:embedContent?key={}",
+            self.model, self.api_key
+        );
+        let body = serde_json::json!({
+            "model": format!("models/{}", self.model),
+            "content": { "parts": [{ "text": text }] },
+        });
+        let response: serde_json::Value = reqwest::Client::new()
+            .post(&url)
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let values = response
+            .get("embedding")
+            .and_then(|e| e.get("values"))
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| anyhow::anyhow!("Gemini embedContent response missing embedding.values"))?;
+
+        values
+            .iter()
+            .map(|v| {
+                v.as_f64()
+                    .map(|f| f as f32)
+                    .ok_or_else(|| anyhow::anyhow!("non-numeric embedding value"))
+            })
+            .collect()
+    }
+}
@@ -23,6 +23,17 @@ pub struct SetupConfig {
     pub tools: Option<Vec<Tool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub context_window_compression: Option<ContextWindowCompression>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_resumption: Option<SessionResumptionConfig>,
+}
+
+/// Requests Gemini Live session resumption. If `handle` is set, this asks Gemini
+/// to continue the conversation identified by a previously issued handle; if it's
+/// `None`, this just opts the new session in to receiving resumption updates.
+#[derive(Debug, Serialize)]
+pub struct SessionResumptionConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub handle: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -159,18 +170,86 @@ pub struct ClientContent {
     pub turn_complete: bool,
 }
 
-/// Server response message.
+/// Server response message. Gemini's Live API sends one top-level key per
+/// message (`setupComplete`, `serverContent`, `toolCall`, ...), so this is
+/// modeled as a tagged enum - via a hand-written `Deserialize` impl that peeks
+/// at which key is present - rather than an all-`Option` struct whose fields
+/// all silently default to `None` for a payload carrying an unrecognized key.
+#[derive(Debug)]
+pub enum ServerMessage {
+    SetupComplete,
+    ServerContent(ServerContent),
+    ToolCall(ToolCall),
+    ToolCallCancellation(ToolCallCancellation),
+    UsageMetadata(UsageMetadata),
+    SessionResumptionUpdate(SessionResumptionUpdate),
+    GoAway(GoAway),
+    /// A top-level key this server doesn't yet model, kept verbatim so the
+    /// caller can log and surface it instead of the message quietly vanishing.
+    Unknown(serde_json::Value),
+}
+
+impl<'de> Deserialize<'de> for ServerMessage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = serde_json::Value::deserialize(deserializer)?;
+        let obj = raw
+            .as_object()
+            .ok_or_else(|| serde::de::Error::custom("expected a Gemini server message object"))?;
+
+        if obj.contains_key("setupComplete") {
+            return Ok(ServerMessage::SetupComplete);
+        }
+
+        macro_rules! variant_from_key {
+            ($key:literal, $variant:ident) => {
+                if let Some(value) = obj.get($key) {
+                    return serde_json::from_value(value.clone())
+                        .map(ServerMessage::$variant)
+                        .map_err(serde::de::Error::custom);
+                }
+            };
+        }
+
+        variant_from_key!("serverContent", ServerContent);
+        variant_from_key!("toolCall", ToolCall);
+        variant_from_key!("toolCallCancellation", ToolCallCancellation);
+        variant_from_key!("usageMetadata", UsageMetadata);
+        variant_from_key!("sessionResumptionUpdate", SessionResumptionUpdate);
+        variant_from_key!("goAway", GoAway);
+
+        Ok(ServerMessage::Unknown(raw))
+    }
+}
+
+/// Gemini asking to cancel one or more previously dispatched tool calls, by id.
+/// No handler currently supports cooperative cancellation, so this is modeled
+/// but only logged - the underlying calls still run to completion.
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ServerMessage {
+pub struct ToolCallCancellation {
     #[serde(default)]
-    pub setup_complete: Option<serde_json::Value>,
+    pub ids: Vec<String>,
+}
+
+/// Periodic update from Gemini carrying a fresh session-resumption handle.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionResumptionUpdate {
     #[serde(default)]
-    pub server_content: Option<ServerContent>,
+    pub new_handle: Option<String>,
     #[serde(default)]
-    pub tool_call: Option<ToolCall>,
+    pub resumable: Option<bool>,
+}
+
+/// Early warning that Gemini is about to close the connection.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoAway {
     #[serde(default)]
-    pub usage_metadata: Option<UsageMetadata>,
+    pub time_left: Option<String>,
 }
 
 /// Token usage metadata from Gemini API.
@@ -220,7 +299,7 @@ pub struct ToolCall {
     pub function_calls: Vec<FunctionCall>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FunctionCall {
     pub name: String,
@@ -231,19 +310,16 @@ pub struct FunctionCall {
 
 /// Tool response message (for sending function call responses back to Gemini).
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
 pub struct ToolResponseMessage {
     pub tool_response: ToolResponse,
 }
 
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
 pub struct ToolResponse {
     pub function_responses: Vec<FunctionResponse>,
 }
 
 #[derive(Debug, Serialize)]
-#[allow(dead_code)]
 pub struct FunctionResponse {
     pub name: String,
     pub id: String,
@@ -263,6 +339,46 @@ pub struct ClientEventMessage {
     pub usage_metadata: Option<ClientUsageMetadata>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_stats: Option<ClientSessionStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_budget_warning: Option<ClientTokenBudgetWarning>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub review_suggestion: Option<ClientReviewSuggestion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ClientErrorEvent>,
+}
+
+/// A structured, machine-readable error surfaced to the browser client over the same
+/// JSON event stream as `server_content`/`tool_call`, rather than as a bare socket
+/// close. `code` is stable across releases so the client can branch on it; `recoverable`
+/// tells the UI whether to offer a reconnect affordance or treat the session as over.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientErrorEvent {
+    pub code: String,
+    pub message: String,
+    pub recoverable: bool,
+}
+
+impl From<&crate::error::AppError> for ClientErrorEvent {
+    fn from(err: &crate::error::AppError) -> Self {
+        use crate::error::AppError;
+
+        let (code, recoverable) = match err {
+            AppError::AuthError(_) => ("auth_failed", false),
+            AppError::InvalidToken => ("auth_failed", false),
+            AppError::SessionExpired => ("session_expired", true),
+            AppError::WebSocketError(_) => ("websocket_error", false),
+            AppError::GeminiError(_) => ("gemini_upstream", true),
+            AppError::ConfigError(_) => ("config_error", false),
+            AppError::Internal(_) => ("internal_error", false),
+        };
+
+        Self {
+            code: code.to_string(),
+            message: err.to_string(),
+            recoverable,
+        }
+    }
 }
 
 /// Session stats snapshot forwarded with tool calls.
@@ -272,6 +388,12 @@ pub struct ClientSessionStats {
     pub message_count: u64,
     pub audio_chunks_sent: u64,
     pub elapsed_seconds: f64,
+    /// Locally tokenized estimate of tokens sent so far (system instruction plus text
+    /// turns), for comparison against `actual_token_count` once Gemini reports usage.
+    pub estimated_token_count: u32,
+    /// Last `total_token_count` actually reported by Gemini's `usage_metadata`, 0 if
+    /// none has arrived yet this connection.
+    pub actual_token_count: u32,
 }
 
 /// Token usage metadata for browser client (camelCase).
@@ -281,6 +403,32 @@ pub struct ClientUsageMetadata {
     pub prompt_token_count: u32,
     pub response_token_count: u32,
     pub total_token_count: u32,
+    /// Locally tokenized estimate of tokens sent so far, for comparison against
+    /// `total_token_count` (the actual count Gemini just reported).
+    pub estimated_token_count: u32,
+}
+
+/// Emitted once cumulative `total_token_count` crosses the configured soft budget
+/// threshold, so the client can warn the learner before the hard cap ends the session.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientTokenBudgetWarning {
+    pub total_token_count: u32,
+    pub soft_limit: u32,
+}
+
+/// Emitted when a learner's utterance closely matches a cluster of prior utterances
+/// in the error-memory store, surfacing a recurring mistake pattern rather than
+/// treating each session in isolation.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientReviewSuggestion {
+    /// The utterance that just triggered this suggestion.
+    pub pattern_text: String,
+    /// Prior utterances judged similar to `pattern_text`, most similar first.
+    pub example_texts: Vec<String>,
+    /// How many similar utterances (including `pattern_text`) make up this cluster.
+    pub occurrence_count: usize,
 }
 
 #[derive(Debug, Serialize)]
@@ -301,6 +449,9 @@ pub struct ClientServerContent {
 pub struct ClientTranscription {
     pub text: String,
     pub finished: bool,
+    /// Position on the relevant media timeline (input or output, milliseconds)
+    /// at which this transcript applies, for caption/audio alignment.
+    pub timestamp_ms: f64,
 }
 
 #[derive(Debug, Serialize)]
@@ -351,18 +502,14 @@ mod tests {
 
         let msg: ServerMessage = serde_json::from_str(json).expect("Failed to parse");
 
-        assert!(msg.server_content.is_some(), "server_content should be Some");
-
-        let server_content = msg.server_content.unwrap();
-        assert!(server_content.model_turn.is_some(), "model_turn should be Some");
-
-        let model_turn = server_content.model_turn.unwrap();
+        let ServerMessage::ServerContent(server_content) = msg else {
+            panic!("expected ServerContent variant, got {:?}", msg);
+        };
+        let model_turn = server_content.model_turn.expect("model_turn should be Some");
         assert_eq!(model_turn.parts.len(), 1, "Should have 1 part");
 
         let part = &model_turn.parts[0];
-        assert!(part.inline_data.is_some(), "inline_data should be Some");
-
-        let inline_data = part.inline_data.as_ref().unwrap();
+        let inline_data = part.inline_data.as_ref().expect("inline_data should be Some");
         assert_eq!(inline_data.mime_type, "audio/pcm;rate=24000");
         assert_eq!(inline_data.data, "AAAA");
 
@@ -373,7 +520,7 @@ mod tests {
     fn test_parse_setup_complete() {
         let json = r#"{"setupComplete": {}}"#;
         let msg: ServerMessage = serde_json::from_str(json).expect("Failed to parse");
-        assert!(msg.setup_complete.is_some());
+        assert!(matches!(msg, ServerMessage::SetupComplete));
         println!("✅ Setup complete parsing works!");
     }
 
@@ -387,10 +534,13 @@ mod tests {
             }
         }"#;
         let msg: ServerMessage = serde_json::from_str(json).expect("Failed to parse");
-        assert!(msg.server_content.is_some());
-        let content = msg.server_content.unwrap();
-        assert!(content.output_transcription.is_some());
-        assert_eq!(content.output_transcription.unwrap().text, "Hello world");
+        let ServerMessage::ServerContent(content) = msg else {
+            panic!("expected ServerContent variant, got {:?}", msg);
+        };
+        let output_transcription = content
+            .output_transcription
+            .expect("output_transcription should be Some");
+        assert_eq!(output_transcription.text, "Hello world");
         println!("✅ Transcription parsing works!");
     }
 }
@@ -85,6 +85,11 @@
 //! - [Audio Format Guide](https://ai.google.dev/api/multimodal-live#audio)
 
 mod client;
+mod embeddings;
 mod messages;
+mod tokenizer;
+mod tools;
+mod transcription;
 
 pub use client::GeminiLiveClient;
+pub use messages::{ClientErrorEvent, ClientEventMessage};
@@ -0,0 +1,37 @@
+//! Local token counting for text sent to Gemini.
+//!
+//! `usage_metadata` only arrives after Gemini has already processed a turn, so there
+//! was no way to estimate (let alone cap) cost before audio/text actually goes out.
+//! This counts tokens client-side with the same BPE Gemini's family of models is
+//! roughly compatible with, so `client.rs` can report an estimate alongside Gemini's
+//! own (authoritative) counts.
+
+use tiktoken_rs::{cl100k_base, CoreBPE};
+
+/// Counts tokens in a piece of text, skipping the re-tokenize if the text is exactly
+/// the text last counted (e.g. the same system instruction replayed on every reconnect).
+pub struct Tokenizer {
+    bpe: CoreBPE,
+    last_text: String,
+    last_count: usize,
+}
+
+impl Tokenizer {
+    pub fn new() -> anyhow::Result<Self> {
+        Ok(Self {
+            bpe: cl100k_base()?,
+            last_text: String::new(),
+            last_count: 0,
+        })
+    }
+
+    /// Count tokens in `text`, reusing the cached count if `text` is unchanged since
+    /// the last call.
+    pub fn count(&mut self, text: &str) -> usize {
+        if text != self.last_text {
+            self.last_count = self.bpe.encode_ordinary(text).len();
+            self.last_text = text.to_string();
+        }
+        self.last_count
+    }
+}
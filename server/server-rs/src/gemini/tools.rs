@@ -0,0 +1,210 @@
+//! Server-side dispatch for Gemini Live function calls.
+//!
+//! Previously `client.rs` just forwarded `tool_call.function_calls` on to the browser
+//! client and never executed anything or replied to Gemini, so a model that asked for
+//! a function would stall waiting for a `toolResponse` that never came. `ToolRegistry`
+//! maps each function name to an async handler, runs it when a `FunctionCall` comes in,
+//! and builds the `ToolResponseMessage` Gemini expects back.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures_util::future::join_all;
+use serde_json::json;
+
+use crate::session_store::SessionStore;
+
+use super::messages::{FunctionCall, FunctionDeclaration, FunctionResponse, ToolResponse, ToolResponseMessage};
+
+/// One server-side implementation of a function declared in the setup message's `tools`.
+#[async_trait]
+pub trait ToolHandler: Send + Sync {
+    /// The `FunctionDeclaration` advertised to Gemini in `SetupConfig.tools` so the
+    /// model knows this function exists and how to call it.
+    fn declaration(&self) -> FunctionDeclaration;
+
+    /// Run the call and return the value to send back to Gemini as `response`.
+    async fn call(&self, session_id: &str, args: serde_json::Value) -> anyhow::Result<serde_json::Value>;
+}
+
+/// Maps function names to the handler that executes them.
+#[derive(Clone, Default)]
+pub struct ToolRegistry {
+    handlers: HashMap<String, Arc<dyn ToolHandler>>,
+}
+
+impl ToolRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, name: impl Into<String>, handler: Arc<dyn ToolHandler>) {
+        self.handlers.insert(name.into(), handler);
+    }
+
+    /// The starter set of tools useful for immersive language learning.
+    pub fn with_builtin_tools(session_store: Arc<SessionStore>) -> Self {
+        let mut registry = Self::new();
+        registry.register("lookup_word", Arc::new(LookupWordTool));
+        registry.register("correct_grammar", Arc::new(CorrectGrammarTool));
+        registry.register("save_vocabulary", Arc::new(SaveVocabularyTool { session_store }));
+        registry
+    }
+
+    /// The `FunctionDeclaration` for every registered tool, for the setup message's
+    /// `SetupConfig.tools` list.
+    pub fn function_declarations(&self) -> Vec<FunctionDeclaration> {
+        self.handlers.values().map(|handler| handler.declaration()).collect()
+    }
+
+    /// Run every function call from one `toolCall` message concurrently - Gemini can
+    /// ask for several functions in a single turn and expects all of them back in one
+    /// `toolResponse` - and build the combined response. A call for an unregistered
+    /// name gets an `{"error": "..."}` response rather than failing the whole batch.
+    pub async fn dispatch(&self, session_id: &str, function_calls: Vec<FunctionCall>) -> ToolResponseMessage {
+        let calls = join_all(function_calls.into_iter().map(|fc| async move {
+            let response = match self.handlers.get(&fc.name) {
+                Some(handler) => match handler.call(session_id, fc.args.unwrap_or_else(|| json!({}))).await {
+                    Ok(result) => result,
+                    Err(e) => {
+                        tracing::warn!("Tool '{}' failed: {}", fc.name, e);
+                        json!({ "error": e.to_string() })
+                    }
+                },
+                None => {
+                    tracing::warn!("No handler registered for tool '{}'", fc.name);
+                    json!({ "error": format!("Unknown tool: {}", fc.name) })
+                }
+            };
+            FunctionResponse { name: fc.name, id: fc.id, response }
+        }));
+
+        let function_responses = calls.await;
+        ToolResponseMessage { tool_response: ToolResponse { function_responses } }
+    }
+}
+
+/// Looks up a word's definition and IPA pronunciation. No dictionary backend is wired
+/// in yet, so this is a stub the model can still narrate from until one is added.
+struct LookupWordTool;
+
+#[async_trait]
+impl ToolHandler for LookupWordTool {
+    fn declaration(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: "lookup_word".to_string(),
+            description: Some("Looks up a word's definition and IPA pronunciation.".to_string()),
+            parameters: Some(json!({
+                "type": "object",
+                "properties": {
+                    "term": { "type": "string", "description": "The word or phrase to look up." },
+                    "lang": { "type": "string", "description": "BCP-47 language code the term is in. Defaults to \"en\"." },
+                },
+                "required": ["term"],
+            })),
+        }
+    }
+
+    async fn call(&self, _session_id: &str, args: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        let term = args.get("term").and_then(|v| v.as_str()).unwrap_or_default();
+        let lang = args.get("lang").and_then(|v| v.as_str()).unwrap_or("en");
+        Ok(json!({
+            "term": term,
+            "lang": lang,
+            "definition": format!("No dictionary backend configured; echoing '{}' back.", term),
+            "ipa": serde_json::Value::Null,
+        }))
+    }
+}
+
+/// Corrects a sentence's capitalization and terminal punctuation and reports a
+/// word-level diff. A rule-based placeholder until a real grammar model is wired in.
+struct CorrectGrammarTool;
+
+#[async_trait]
+impl ToolHandler for CorrectGrammarTool {
+    fn declaration(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: "correct_grammar".to_string(),
+            description: Some("Corrects a sentence's capitalization and terminal punctuation, reporting a word-level diff.".to_string()),
+            parameters: Some(json!({
+                "type": "object",
+                "properties": {
+                    "sentence": { "type": "string", "description": "The sentence to correct." },
+                },
+                "required": ["sentence"],
+            })),
+        }
+    }
+
+    async fn call(&self, _session_id: &str, args: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        let sentence = args.get("sentence").and_then(|v| v.as_str()).unwrap_or_default();
+        let corrected = capitalize_and_punctuate(sentence);
+        Ok(json!({
+            "original": sentence,
+            "corrected": corrected,
+            "diff": word_diff(sentence, &corrected),
+        }))
+    }
+}
+
+fn capitalize_and_punctuate(sentence: &str) -> String {
+    let trimmed = sentence.trim();
+    if trimmed.is_empty() {
+        return String::new();
+    }
+    let mut chars = trimmed.chars();
+    let mut corrected = match chars.next() {
+        Some(c) => c.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    };
+    if !corrected.ends_with(['.', '!', '?']) {
+        corrected.push('.');
+    }
+    corrected
+}
+
+/// Word-level diff between the original and corrected sentence, one entry per
+/// position whose word changed.
+fn word_diff(original: &str, corrected: &str) -> Vec<serde_json::Value> {
+    let before: Vec<&str> = original.split_whitespace().collect();
+    let after: Vec<&str> = corrected.split_whitespace().collect();
+    before
+        .iter()
+        .zip(after.iter())
+        .enumerate()
+        .filter(|(_, (b, a))| b != a)
+        .map(|(i, (b, a))| json!({ "position": i, "before": b, "after": a }))
+        .collect()
+}
+
+/// Persists a term and the context it came up in to the learner's saved-vocabulary deck.
+struct SaveVocabularyTool {
+    session_store: Arc<SessionStore>,
+}
+
+#[async_trait]
+impl ToolHandler for SaveVocabularyTool {
+    fn declaration(&self) -> FunctionDeclaration {
+        FunctionDeclaration {
+            name: "save_vocabulary".to_string(),
+            description: Some("Saves a term and the context it came up in to the learner's saved-vocabulary deck.".to_string()),
+            parameters: Some(json!({
+                "type": "object",
+                "properties": {
+                    "term": { "type": "string", "description": "The word or phrase to save." },
+                    "context": { "type": "string", "description": "The sentence or phrase the term appeared in." },
+                },
+                "required": ["term"],
+            })),
+        }
+    }
+
+    async fn call(&self, session_id: &str, args: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        let term = args.get("term").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let context = args.get("context").and_then(|v| v.as_str()).map(str::to_string);
+        self.session_store.save_vocabulary(session_id, &term, context.as_deref()).await?;
+        Ok(json!({ "saved": true, "term": term }))
+    }
+}
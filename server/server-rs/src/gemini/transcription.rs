@@ -0,0 +1,177 @@
+//! Pluggable fallback for input (user-speech) transcription.
+//!
+//! Gemini's own input transcription is fragile - `client.rs` already has to treat
+//! "transcription + tools" as a known cause of early policy closes - so deployments
+//! that hit that wall, or that simply disable Gemini transcription, can tee the same
+//! PCM audio that's sent to Gemini into a separate speech-to-text backend instead.
+//! Results are wrapped in the exact same `ClientEventMessage`/`ClientServerContent`
+//! shape Gemini's native transcription produces, so the browser client needs no
+//! changes to consume them.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use aws_sdk_transcribestreaming::types::{AudioEvent, AudioStream, LanguageCode, MediaEncoding};
+use aws_sdk_transcribestreaming::primitives::Blob;
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+
+use crate::{config::Config, handlers::websocket::ClientEvent};
+
+use super::messages::{ClientEventMessage, ClientServerContent, ClientTranscription};
+
+/// A speech-to-text backend that the audio-sender task tees client PCM into, in
+/// addition to (or instead of) Gemini's own transcription.
+#[async_trait]
+pub trait InputTranscriber: Send + Sync {
+    /// Push one chunk of 16-bit PCM audio at `config.input_sample_rate`. Fire-and-forget:
+    /// implementations report results asynchronously via the `event_tx` they were built
+    /// with rather than through this call's return value.
+    async fn push_audio(&self, pcm: &[u8]);
+}
+
+/// Build the configured fallback `InputTranscriber` for a new session, if any.
+///
+/// `"none"` (the default) returns `None`, leaving Gemini's native transcription as the
+/// only source of input captions. Unrecognized values also disable the fallback rather
+/// than failing the connection.
+///
+/// `timestamp_ms` reads the caller's input media clock at the moment a result is
+/// reported, so fallback captions carry the same playout-aligned timestamp Gemini's own
+/// transcription does instead of an arrival-order placeholder.
+pub fn build_transcriber(
+    config: &Config,
+    event_tx: mpsc::Sender<ClientEvent>,
+    timestamp_ms: impl Fn() -> f64 + Send + Sync + 'static,
+) -> Option<Arc<dyn InputTranscriber>> {
+    match config.transcription_backend.as_str() {
+        "aws_transcribe" => Some(Arc::new(AwsTranscriber::new(
+            config.input_sample_rate,
+            event_tx,
+            timestamp_ms,
+        ))),
+        "none" | "" => None,
+        other => {
+            tracing::warn!("Unknown TRANSCRIPTION_BACKEND '{}', disabling fallback transcription", other);
+            None
+        }
+    }
+}
+
+/// Input transcription backed by Amazon Transcribe streaming.
+struct AwsTranscriber {
+    audio_tx: mpsc::Sender<Vec<u8>>,
+}
+
+impl AwsTranscriber {
+    fn new(
+        sample_rate: u32,
+        event_tx: mpsc::Sender<ClientEvent>,
+        timestamp_ms: impl Fn() -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        let (audio_tx, audio_rx) = mpsc::channel::<Vec<u8>>(100);
+        tokio::spawn(Self::run(sample_rate, audio_rx, event_tx, Arc::new(timestamp_ms)));
+        Self { audio_tx }
+    }
+
+    async fn run(
+        sample_rate: u32,
+        audio_rx: mpsc::Receiver<Vec<u8>>,
+        event_tx: mpsc::Sender<ClientEvent>,
+        timestamp_ms: Arc<dyn Fn() -> f64 + Send + Sync>,
+    ) {
+        let aws_config = aws_config::load_from_env().await;
+        let client = aws_sdk_transcribestreaming::Client::new(&aws_config);
+
+        let audio_stream = ReceiverStream::new(audio_rx).map(|pcm| {
+            Ok(AudioStream::AudioEvent(
+                AudioEvent::builder().audio_chunk(Blob::new(pcm)).build(),
+            ))
+        });
+
+        let output = match client
+            .start_stream_transcription()
+            .language_code(LanguageCode::EnUs)
+            .media_sample_rate_hertz(sample_rate as i32)
+            .media_encoding(MediaEncoding::Pcm)
+            .audio_stream(audio_stream.into())
+            .send()
+            .await
+        {
+            Ok(output) => output,
+            Err(e) => {
+                tracing::error!("Failed to start Amazon Transcribe stream: {}", e);
+                return;
+            }
+        };
+
+        let mut transcript_stream = output.transcript_result_stream;
+        loop {
+            match transcript_stream.recv().await {
+                Ok(Some(event)) => {
+                    if let Err(e) = Self::forward_event(event, &event_tx, timestamp_ms()).await {
+                        tracing::error!("Failed to forward Amazon Transcribe event: {}", e);
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::error!("Amazon Transcribe stream error: {}", e);
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn forward_event(
+        event: aws_sdk_transcribestreaming::types::TranscriptResultStream,
+        event_tx: &mpsc::Sender<ClientEvent>,
+        timestamp_ms: f64,
+    ) -> anyhow::Result<()> {
+        let aws_sdk_transcribestreaming::types::TranscriptResultStream::TranscriptEvent(transcript_event) = event
+        else {
+            return Ok(());
+        };
+        let Some(transcript) = transcript_event.transcript else {
+            return Ok(());
+        };
+
+        for result in transcript.results.unwrap_or_default() {
+            let Some(alternative) = result.alternatives.unwrap_or_default().into_iter().next() else {
+                continue;
+            };
+            let Some(text) = alternative.transcript else {
+                continue;
+            };
+
+            let event_msg = ClientEventMessage {
+                server_content: Some(ClientServerContent {
+                    input_transcription: Some(ClientTranscription {
+                        text,
+                        finished: !result.is_partial.unwrap_or(false),
+                        timestamp_ms,
+                    }),
+                    output_transcription: None,
+                    turn_complete: None,
+                    interrupted: None,
+                }),
+                tool_call: None,
+                usage_metadata: None,
+                session_stats: None,
+                token_budget_warning: None,
+                review_suggestion: None,
+                error: None,
+            };
+            let json = serde_json::to_string(&event_msg)?;
+            event_tx.send(ClientEvent::Json(json)).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl InputTranscriber for AwsTranscriber {
+    async fn push_audio(&self, pcm: &[u8]) {
+        let _ = self.audio_tx.send(pcm.to_vec()).await;
+    }
+}
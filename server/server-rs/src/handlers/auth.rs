@@ -8,7 +8,8 @@
 //!
 //! ```json
 //! {
-//!   "session_duration": 180  // Optional, defaults to server config
+//!   "session_duration": 180,  // Optional, defaults to server config
+//!   "join": "share-id"        // Optional, attach as a read-only spectator
 //! }
 //! ```
 //!
@@ -17,7 +18,11 @@
 //! ```json
 //! {
 //!   "session_token": "uuid-v4-token",
-//!   "session_time_limit": 180
+//!   "session_time_limit": 180,
+//!   "share_id": "uuid-v4-share-id",  // Present unless this token is for a spectator
+//!   "provider": "gemini",            // RealtimeBackend that /ws will use this token with
+//!   "replay_credential": "base64-signature" // Present unless this token is for a spectator;
+//!                                            // pass back as `?credential=` to GET /api/sessions/{id}
 //! }
 //! ```
 //!
@@ -26,6 +31,13 @@
 //! 1. Client calls `POST /api/auth` to get a session token
 //! 2. Client connects to `ws://server/ws?token={session_token}`
 //! 3. Token is single-use and expires after 30 seconds
+//!
+//! ## Classroom / Spectator Mode
+//!
+//! The primary learner's token is issued with a `share_id`. Any other client can
+//! call `/api/auth` with `"join": "<share_id>"` to receive a token that, when used
+//! against `/ws?token=...&join=<share_id>`, attaches as a read-only spectator of the
+//! same live Gemini session instead of opening its own upstream connection.
 
 use axum::{extract::State, Json};
 use serde::{Deserialize, Serialize};
@@ -42,12 +54,29 @@ pub struct AuthRequest {
     /// Custom session duration in seconds (optional)
     #[serde(default)]
     session_duration: Option<u64>,
+    /// Share-id of an existing "classroom" session to join as a spectator (optional)
+    #[serde(default)]
+    join: Option<String>,
+    /// Previous session token whose Gemini resumption handle should be replayed
+    /// into the next `/ws` connection, continuing the same conversation (optional)
+    #[serde(default)]
+    resume_token: Option<String>,
 }
 
 #[derive(Serialize)]
 pub struct AuthResponse {
     session_token: String,
     session_time_limit: u64,
+    /// Share-id assigned to this token, absent for spectator tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    share_id: Option<String>,
+    /// The `RealtimeBackend` that will handle the `/ws` session this token authenticates.
+    provider: String,
+    /// Proof that the caller holds this session's token, to present as `?credential=`
+    /// when later calling `GET /api/sessions/{id}` to replay this session's history.
+    /// Absent for spectator tokens, which don't own a session of their own.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replay_credential: Option<String>,
 }
 
 /// Issue a temporary session token for WebSocket authentication.
@@ -61,12 +90,26 @@ pub async fn authenticate(
         .map(|d| d.clamp(MIN_SESSION_DURATION, MAX_SESSION_DURATION))
         .unwrap_or(state.config.session_time_limit);
 
-    let token = state.create_token(session_duration).await;
+    let (token, share_id) = state
+        .create_token(session_duration, request.join.clone(), request.resume_token.clone())
+        .await;
+
+    tracing::info!(
+        "Issued new session token with duration: {}s{}",
+        session_duration,
+        request.join.as_deref().map(|id| format!(" (spectator of {})", id)).unwrap_or_default()
+    );
 
-    tracing::info!("Issued new session token with duration: {}s", session_duration);
+    let replay_credential = request
+        .join
+        .is_none()
+        .then(|| state.session_tokens.sign_opaque(&token));
 
     Ok(Json(AuthResponse {
         session_token: token,
         session_time_limit: session_duration,
+        share_id,
+        provider: state.config.provider.clone(),
+        replay_credential,
     }))
 }
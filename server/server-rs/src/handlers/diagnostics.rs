@@ -0,0 +1,29 @@
+//! Admin-only introspection into recently captured internal errors.
+//!
+//! # Endpoints
+//!
+//! - `GET /api/admin/diagnostics` - the last few `AppError::Internal` occurrences,
+//!   newest last, with their cause chain and backtrace. Requires
+//!   `Authorization: Bearer <ADMIN_API_KEY>`; see `AppState::check_admin_key`.
+
+use axum::{extract::State, http::HeaderMap, Json};
+use serde::Serialize;
+
+use crate::{
+    diagnostics::{self, DiagnosticEntry},
+    error::Result,
+    state::AppState,
+};
+
+#[derive(Serialize)]
+pub struct DiagnosticsResponse {
+    entries: Vec<DiagnosticEntry>,
+}
+
+/// Recent internal errors captured since the server started, for operator triage.
+pub async fn recent_errors(State(state): State<AppState>, headers: HeaderMap) -> Result<Json<DiagnosticsResponse>> {
+    state.check_admin_key(&headers)?;
+    Ok(Json(DiagnosticsResponse {
+        entries: diagnostics::recent(),
+    }))
+}
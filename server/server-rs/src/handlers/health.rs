@@ -9,7 +9,8 @@
 //! ```json
 //! {
 //!   "status": "ok",
-//!   "model": "gemini-2.0-flash-live-001"
+//!   "model": "gemini-2.0-flash-live-001",
+//!   "provider": "gemini"
 //! }
 //! ```
 
@@ -22,11 +23,14 @@ use crate::state::AppState;
 pub struct HealthResponse {
     status: &'static str,
     model: String,
+    /// The `RealtimeBackend` currently configured to drive `/ws` sessions.
+    provider: String,
 }
 
 pub async fn health_check(State(state): State<AppState>) -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "ok",
         model: state.config.model.clone(),
+        provider: state.config.provider.clone(),
     })
 }
@@ -0,0 +1,111 @@
+//! Cross-device history sync endpoints, backed by `HistoryStore`.
+//!
+//! Both endpoints require `Authorization: Bearer <account_id>`, the same shared
+//! identifier across every device syncing to one account (e.g. derived from the
+//! sync encryption key pairing in `apps/native/src-tauri/src/sync.rs`). The server
+//! doesn't validate it against a user directory - it only uses it to partition
+//! `history_records` so one account's rows are never visible to, or overwritable
+//! by, a request presenting a different one.
+//!
+//! - `POST /api/history` - push a batch of records (including tombstones); each
+//!   is applied only if its `version` is greater than what's stored, except a
+//!   tombstone which always wins.
+//! - `GET /api/history?after=<cursor>&limit=<n>` - pull every record whose
+//!   server-side timestamp is greater than `after`, oldest first, capped at
+//!   `limit`. The response's `cursor` is where the next call should resume from.
+
+use axum::extract::{Query, State};
+use axum::http::{header::AUTHORIZATION, HeaderMap};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{AppError, Result},
+    history_store::{HistoryRecord, HistoryRecordOut},
+    state::AppState,
+};
+
+const DEFAULT_PULL_LIMIT: usize = 200;
+const MAX_PULL_LIMIT: usize = 1000;
+
+/// Extract the account id every `/api/history` call must present as
+/// `Authorization: Bearer <account_id>`.
+fn require_account_id(headers: &HeaderMap) -> Result<String> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| {
+            AppError::AuthError("Missing or empty Authorization bearer account id".to_string())
+        })
+}
+
+#[derive(Deserialize)]
+pub struct PushRecord {
+    table_name: String,
+    row_id: String,
+    data: serde_json::Value,
+    version: i64,
+    #[serde(default)]
+    deleted: bool,
+}
+
+#[derive(Deserialize)]
+pub struct PushRequest {
+    records: Vec<PushRecord>,
+}
+
+#[derive(Serialize)]
+pub struct PushResponse {
+    synced_count: usize,
+}
+
+/// Push a batch of local records (including tombstones) for the server to merge.
+pub async fn push_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<PushRequest>,
+) -> Result<Json<PushResponse>> {
+    let account_id = require_account_id(&headers)?;
+    let records = body
+        .records
+        .into_iter()
+        .map(|r| HistoryRecord {
+            table_name: r.table_name,
+            row_id: r.row_id,
+            data: r.data,
+            version: r.version,
+            deleted: r.deleted,
+        })
+        .collect();
+    let synced_count = state.history_store.push(&account_id, records).await?;
+    Ok(Json(PushResponse { synced_count }))
+}
+
+#[derive(Deserialize)]
+pub struct PullQuery {
+    after: Option<i64>,
+    limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct PullResponse {
+    records: Vec<HistoryRecordOut>,
+    cursor: i64,
+}
+
+/// Pull every record added or changed since `after`, paginated by `limit`.
+pub async fn pull_history(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<PullQuery>,
+) -> Result<Json<PullResponse>> {
+    let account_id = require_account_id(&headers)?;
+    let after = query.after.unwrap_or(0);
+    let limit = query.limit.unwrap_or(DEFAULT_PULL_LIMIT).min(MAX_PULL_LIMIT);
+    let records = state.history_store.pull(&account_id, after, limit).await?;
+    let cursor = records.last().map(|r| r.updated_at).unwrap_or(after);
+    Ok(Json(PullResponse { records, cursor }))
+}
@@ -0,0 +1,10 @@
+//! HTTP/WebSocket request handlers.
+
+pub mod auth;
+pub mod diagnostics;
+pub mod health;
+pub mod history;
+pub mod session_tokens;
+pub mod sessions;
+pub mod status;
+pub mod websocket;
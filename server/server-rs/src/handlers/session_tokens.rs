@@ -0,0 +1,53 @@
+//! Admin minting of signed `scope_token`s (see `session_token`), pinning a future
+//! `/ws` connection to one model/voice.
+//!
+//! # Endpoints
+//!
+//! - `POST /api/admin/session-tokens` - mint a `scope_token` for a given
+//!   `session_id`/`model`/`voice_name`, to hand to a trusted client out-of-band.
+//!   Requires `Authorization: Bearer <ADMIN_API_KEY>`; see `AppState::check_admin_key`.
+
+use axum::{extract::State, http::HeaderMap, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Result, session_token::SessionTokenClaims, state::AppState};
+
+/// Default scope-token lifetime if the caller doesn't specify one (5 minutes).
+const DEFAULT_TTL_SECONDS: i64 = 300;
+
+#[derive(Deserialize)]
+pub struct MintSessionTokenRequest {
+    session_id: String,
+    model: String,
+    voice_name: String,
+    /// How long the minted token should remain valid for, in seconds (optional).
+    #[serde(default)]
+    ttl_seconds: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct MintSessionTokenResponse {
+    scope_token: String,
+}
+
+/// Mint a signed `scope_token` for an admin-specified session/model/voice.
+pub async fn mint_session_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<MintSessionTokenRequest>,
+) -> Result<Json<MintSessionTokenResponse>> {
+    state.check_admin_key(&headers)?;
+
+    let ttl = request.ttl_seconds.unwrap_or(DEFAULT_TTL_SECONDS);
+    let exp = crate::session_token::now_secs() + ttl;
+
+    let claims = SessionTokenClaims {
+        session_id: request.session_id,
+        model: request.model,
+        voice_name: request.voice_name,
+        exp,
+    };
+
+    let scope_token = state.session_tokens.mint(&claims)?;
+    Ok(Json(MintSessionTokenResponse { scope_token }))
+}
@@ -0,0 +1,63 @@
+//! Session history query handlers, backed by `SessionStore`.
+//!
+//! # Endpoints
+//!
+//! - `GET /api/sessions/{id}?credential=...` - replay every turn of a past session.
+//!   `credential` must be the `replay_credential` that `/api/auth` issued alongside
+//!   this session's token; see `session_token::SessionTokenIssuer::sign_opaque`.
+//! - `GET /api/sessions/stats` - aggregate token spend across every recorded session,
+//!   gated by the same `ADMIN_API_KEY` bearer auth as `/api/admin/diagnostics`, since
+//!   it exposes spend across every learner's sessions, not just the caller's own.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{AppError, Result},
+    session_store::TurnReplay,
+    state::AppState,
+};
+
+#[derive(Deserialize)]
+pub struct ReplayQuery {
+    /// Proof of ownership issued by `/api/auth` as `replay_credential`, signed over
+    /// this same `session_id`.
+    credential: String,
+}
+
+#[derive(Serialize)]
+pub struct SessionReplayResponse {
+    turns: Vec<TurnReplay>,
+}
+
+/// Replay a past session's turns in order, for a learner reviewing an old conversation.
+/// Requires `credential` to be the `replay_credential` issued for this exact session id,
+/// so one learner can't replay another's session by guessing/enumerating session ids.
+pub async fn replay_session(
+    State(state): State<AppState>,
+    Path(session_id): Path<String>,
+    Query(query): Query<ReplayQuery>,
+) -> Result<Json<SessionReplayResponse>> {
+    if !state.session_tokens.verify_opaque(&session_id, &query.credential) {
+        return Err(AppError::InvalidToken);
+    }
+
+    let turns = state.session_store.replay_session(&session_id).await?;
+    Ok(Json(SessionReplayResponse { turns }))
+}
+
+#[derive(Serialize)]
+pub struct TokenUsageResponse {
+    total_tokens: i64,
+}
+
+/// Aggregate token spend across every session this server has recorded.
+pub async fn token_usage(State(state): State<AppState>, headers: HeaderMap) -> Result<Json<TokenUsageResponse>> {
+    state.check_admin_key(&headers)?;
+    let total_tokens = state.session_store.total_tokens_spent().await?;
+    Ok(Json(TokenUsageResponse { total_tokens }))
+}
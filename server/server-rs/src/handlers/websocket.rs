@@ -34,7 +34,10 @@
 //! ### Client → Server
 //!
 //! - **Binary**: Raw PCM audio (16-bit, 16kHz, mono)
-//! - **Text (JSON)**: Setup config or text messages
+//! - **Text (JSON)**: Tagged [`crate::protocol::InboundMessage`] frames (setup config,
+//!   base64 audio chunks, text input, VAD markers, keepalive pings). Malformed frames are
+//!   rejected with a structured [`crate::protocol::OutboundMessage::Error`] reply instead
+//!   of being silently dropped.
 //!
 //! ### Server → Client
 //!
@@ -45,20 +48,34 @@ use axum::{
     extract::{ws::WebSocket, Query, State, WebSocketUpgrade},
     response::Response,
 };
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use futures_util::{SinkExt, StreamExt};
 use serde::Deserialize;
-use std::time::Duration;
-use tokio::sync::mpsc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::{broadcast, mpsc, Mutex as AsyncMutex};
 
 use crate::{
+    backend::build_backend,
     error::{AppError, Result},
-    gemini::GeminiLiveClient,
+    gemini::{ClientErrorEvent, ClientEventMessage},
+    protocol,
+    session_token::SessionTokenClaims,
     state::AppState,
 };
 
 #[derive(Deserialize)]
 pub struct WsQuery {
     token: Option<String>,
+    /// Share-id to join as a read-only spectator instead of driving a new session.
+    /// Overrides the join target embedded in the token, if any.
+    join: Option<String>,
+    /// Optional signed `SessionTokenClaims`, minted by a trusted front door, pinning
+    /// this connection to one model/voice regardless of what the client's own setup
+    /// message requests. See `session_token`.
+    scope_token: Option<String>,
 }
 
 /// WebSocket upgrade handler.
@@ -69,78 +86,238 @@ pub async fn ws_handler(
     State(state): State<AppState>,
     Query(query): Query<WsQuery>,
 ) -> Result<Response> {
-    // Validate token and get session duration
+    // Validate token and get session parameters
     let token = query.token.ok_or(AppError::InvalidToken)?;
 
-    let session_duration = state
+    let consumed = state
         .consume_token(&token)
         .await
         .ok_or(AppError::InvalidToken)?;
 
+    // A scope token further pins the session to one model/voice; a bad signature or
+    // an expired claim rejects the upgrade before any Gemini connection is opened.
+    let scope = query
+        .scope_token
+        .as_deref()
+        .map(|t| state.session_tokens.verify(t))
+        .transpose()?;
+
+    let join_target = query.join.or(consumed.join);
+
+    if let Some(share_id) = join_target {
+        let rx = state
+            .subscribe_share(&share_id)
+            .await
+            .ok_or_else(|| AppError::WebSocketError(format!("Unknown classroom session: {}", share_id)))?;
+
+        tracing::info!("Spectator joining classroom session {}", share_id);
+
+        return Ok(ws.on_upgrade(move |socket| handle_spectator_socket(socket, rx)));
+    }
+
+    let share_id = consumed.share_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
     tracing::info!(
-        "WebSocket connection authenticated with session duration: {}s",
-        session_duration
+        "WebSocket connection authenticated with session duration: {}s (share_id: {})",
+        consumed.duration,
+        share_id
     );
 
-    Ok(ws.on_upgrade(move |socket| handle_socket(socket, state, session_duration)))
+    Ok(ws.on_upgrade(move |socket| {
+        handle_socket(socket, state, consumed.duration, share_id, token, consumed.resume_handle, scope)
+    }))
 }
 
 /// Handle the WebSocket connection.
-async fn handle_socket(socket: WebSocket, state: AppState, session_duration: u64) {
-    if let Err(e) = handle_socket_inner(socket, state, session_duration).await {
+#[allow(clippy::too_many_arguments)]
+async fn handle_socket(
+    socket: WebSocket,
+    state: AppState,
+    session_duration: u64,
+    share_id: String,
+    session_token: String,
+    resume_handle: Option<String>,
+    scope: Option<SessionTokenClaims>,
+) {
+    if let Err(e) =
+        handle_socket_inner(socket, state, session_duration, share_id, session_token, resume_handle, scope).await
+    {
         tracing::error!("WebSocket session error: {}", e);
     }
 }
 
+/// Force the client's raw setup JSON to request exactly `voice_name`, creating the
+/// `generation_config.speech_config.voice_config.prebuilt_voice_config` path if the
+/// client's setup didn't include one at all. Indexing a `Value::Null` with a string
+/// key promotes it to an object in place, so this builds out whatever prefix of the
+/// path is missing without disturbing sibling fields the client did set.
+fn apply_voice_scope(setup_config: &mut Option<serde_json::Value>, voice_name: &str) {
+    let setup = setup_config.get_or_insert_with(|| serde_json::Value::Null);
+    setup["generation_config"]["speech_config"]["voice_config"]["prebuilt_voice_config"]["voice_name"] =
+        serde_json::Value::String(voice_name.to_string());
+}
+
+/// Forward a read-only spectator's broadcast stream to its WebSocket.
+///
+/// Spectators never get `audio_tx`/`text_tx` channels, so anything they send
+/// upstream is simply dropped here - they can only observe.
+async fn handle_spectator_socket(socket: WebSocket, mut events: broadcast::Receiver<ClientEvent>) {
+    let (mut ws_sender, mut ws_receiver) = socket.split();
+
+    loop {
+        tokio::select! {
+            msg = ws_receiver.next() => {
+                match msg {
+                    Some(Ok(axum::extract::ws::Message::Close(_))) | None => break,
+                    Some(Err(_)) => break,
+                    // Spectators cannot push audio/text upstream; anything else is ignored.
+                    _ => {}
+                }
+            }
+            event = events.recv() => {
+                match event {
+                    Ok(ClientEvent::Audio { data, timestamp_ms, duration_ms }) => {
+                        let ts_json = serde_json::json!({
+                            "audioTimestamp": { "startMs": timestamp_ms, "durationMs": duration_ms }
+                        }).to_string();
+                        if ws_sender.send(axum::extract::ws::Message::Text(ts_json.into())).await.is_err() {
+                            break;
+                        }
+                        if ws_sender.send(axum::extract::ws::Message::Binary(data.into())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(ClientEvent::Json(json)) => {
+                        if ws_sender.send(axum::extract::ws::Message::Text(json.into())).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(ClientEvent::Error { message, stats: _ }) => {
+                        let err = AppError::GeminiError(message);
+                        let event_msg = ClientEventMessage {
+                            server_content: None,
+                            tool_call: None,
+                            usage_metadata: None,
+                            session_stats: None,
+                            token_budget_warning: None,
+                            review_suggestion: None,
+                            error: Some(ClientErrorEvent::from(&err)),
+                        };
+                        if let Ok(json) = serde_json::to_string(&event_msg) {
+                            let _ = ws_sender.send(axum::extract::ws::Message::Text(json.into())).await;
+                        }
+                    }
+                    Ok(ClientEvent::SessionEnd { stats }) => {
+                        let end_json = serde_json::json!({ "sessionEnd": { "stats": stats } }).to_string();
+                        let _ = ws_sender.send(axum::extract::ws::Message::Text(end_json.into())).await;
+                    }
+                    Ok(ClientEvent::Close) | Err(broadcast::error::RecvError::Closed) => {
+                        let _ = ws_sender.send(axum::extract::ws::Message::Close(None)).await;
+                        break;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("Spectator lagged behind classroom stream, dropped {} events", skipped);
+                    }
+                    // Resume handles/goAway notices are only meaningful to the primary
+                    // driver, which is the one that will reconnect; spectators just
+                    // keep watching the same share-id once it reconnects.
+                    Ok(ClientEvent::ResumeHandle(_))
+                    | Ok(ClientEvent::GoAway { .. })
+                    | Ok(ClientEvent::Reconnecting { .. }) => {}
+                }
+            }
+        }
+    }
+
+    tracing::info!("Spectator session ended");
+}
+
+/// Tracks liveness for the keepalive ticker and inactivity timeout.
+///
+/// `last_frame` covers *any* WebSocket frame (including Ping/Pong), used to detect a
+/// silently dead client. `last_media` only advances on audio/text flowing in either
+/// direction, used to end abandoned-but-technically-alive sessions.
+#[derive(Clone)]
+struct ConnectionActivity {
+    last_frame: Arc<AsyncMutex<Instant>>,
+    last_media: Arc<AsyncMutex<Instant>>,
+}
+
+impl ConnectionActivity {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            last_frame: Arc::new(AsyncMutex::new(now)),
+            last_media: Arc::new(AsyncMutex::new(now)),
+        }
+    }
+
+    async fn touch_frame(&self) {
+        *self.last_frame.lock().await = Instant::now();
+    }
+
+    async fn touch_media(&self) {
+        self.touch_frame().await;
+        *self.last_media.lock().await = Instant::now();
+    }
+
+    async fn idle_since_frame(&self) -> Duration {
+        self.last_frame.lock().await.elapsed()
+    }
+
+    async fn idle_since_media(&self) -> Duration {
+        self.last_media.lock().await.elapsed()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn handle_socket_inner(
     socket: WebSocket,
     state: AppState,
     session_duration: u64,
+    share_id: String,
+    session_token: String,
+    resume_handle: Option<String>,
+    scope: Option<SessionTokenClaims>,
 ) -> anyhow::Result<()> {
     let (mut ws_sender, mut ws_receiver) = socket.split();
 
-    // Wait for setup message from client
-    let setup_config = match ws_receiver.next().await {
-        Some(Ok(msg)) => {
-            if let axum::extract::ws::Message::Text(text) = msg {
-                let data: serde_json::Value = serde_json::from_str(&text)?;
-                data.get("setup").cloned()
-            } else {
+    // Wait for the typed Setup message from the client.
+    let mut setup_config = match ws_receiver.next().await {
+        Some(Ok(axum::extract::ws::Message::Text(text))) => match protocol::parse_inbound(&text) {
+            Ok(protocol::InboundMessage::Setup { setup }) => {
+                tracing::info!("Received client setup");
+                Some(setup)
+            }
+            Ok(other) => {
+                tracing::warn!("Expected Setup as the first message, got {:?}", other);
+                None
+            }
+            Err(e) => {
+                tracing::warn!("Failed to parse setup message: {}", e);
                 None
             }
+        },
+        _ => {
+            tracing::info!("Received setup config: None");
+            None
         }
-        _ => None,
     };
 
-    // Log feature flags from setup config
-    if let Some(ref config) = setup_config {
-        let has_input_transcription = config
-            .get("realtimeInputConfig")
-            .and_then(|c| c.get("automaticActivityDetection"))
-            .and_then(|c| c.get("speechConfig"))
-            .and_then(|c| c.get("voiceActivityDetection"))
-            .is_some()
-            || config
-                .get("input_audio_transcription")
-                .map(|v| !v.is_null())
-                .unwrap_or(false);
-        let has_output_transcription = config
-            .get("output_audio_transcription")
-            .map(|v| !v.is_null())
-            .unwrap_or(false);
-        let has_tools = config
-            .get("tools")
-            .map(|v| !v.is_null() && v.as_array().map(|a| !a.is_empty()).unwrap_or(false))
-            .unwrap_or(false);
-
-        tracing::info!(
-            "Client setup - input_transcription: {}, output_transcription: {}, has_tools: {}",
-            has_input_transcription,
-            has_output_transcription,
-            has_tools
-        );
-    } else {
-        tracing::info!("Received setup config: None");
+    // A scope token pins the connection to one model/voice/session; enforce it on
+    // the client's setup config regardless of what the client itself asked for,
+    // rather than trusting the client to honor a restriction it was never told
+    // about. Checking `session_id` against this connection's `share_id` is what
+    // makes the token single-session: without it, a token minted for one session
+    // would verify and apply on any other `/ws` connection using the same model.
+    if let Some(claims) = &scope {
+        if claims.model != state.config.model {
+            return Err(AppError::InvalidToken.into());
+        }
+        if claims.session_id != share_id {
+            return Err(AppError::InvalidToken.into());
+        }
+        apply_voice_scope(&mut setup_config, &claims.voice_name);
     }
 
     // Create channels for communication
@@ -148,55 +325,135 @@ async fn handle_socket_inner(
     let (text_tx, text_rx) = mpsc::channel::<String>(100);
     let (event_tx, mut event_rx) = mpsc::channel::<ClientEvent>(100);
 
-    // Connect to Gemini Live API
-    let gemini_client = GeminiLiveClient::new(
+    // Register this session's broadcast fan-out so spectators can subscribe to it.
+    let (spectator_tx, _) = broadcast::channel::<ClientEvent>(AppState::spectator_channel_capacity());
+    state.register_share(share_id.clone(), spectator_tx.clone()).await;
+
+    // Build the configured realtime backend (Gemini today; see `crate::backend`).
+    let realtime_backend = build_backend(
         state.config.clone(),
         setup_config,
         audio_rx,
         text_rx,
         event_tx,
+        state.clone(),
+        session_token,
+        resume_handle,
     );
 
-    // Spawn Gemini session task
+    // Spawn the backend session task
     let session_handle = tokio::spawn(async move {
-        if let Err(e) = gemini_client.run().await {
-            tracing::error!("Gemini session error: {}", e);
+        if let Err(e) = realtime_backend.run().await {
+            tracing::error!("Realtime backend session error: {}", e);
         }
     });
 
+    // Shared liveness tracker for the keepalive ticker and inactivity timeout.
+    let activity = ConnectionActivity::new();
+    let receive_activity = activity.clone();
+    let protocol_event_tx = event_tx.clone();
+
     // Spawn task to receive from client
     let receive_handle = tokio::spawn(async move {
         while let Some(msg) = ws_receiver.next().await {
             match msg {
                 Ok(axum::extract::ws::Message::Binary(data)) => {
                     // Audio data
+                    receive_activity.touch_media().await;
                     let _ = audio_tx.send(data.to_vec()).await;
                 }
                 Ok(axum::extract::ws::Message::Text(text)) => {
-                    // Text or JSON message
-                    let _ = text_tx.send(text.to_string()).await;
+                    receive_activity.touch_media().await;
+                    match protocol::parse_inbound(&text) {
+                        Ok(protocol::InboundMessage::AudioChunk { data }) => {
+                            match BASE64.decode(&data) {
+                                Ok(bytes) => {
+                                    let _ = audio_tx.send(bytes).await;
+                                }
+                                Err(e) => {
+                                    tracing::warn!("Failed to decode audioChunk data: {}", e);
+                                }
+                            }
+                        }
+                        Ok(protocol::InboundMessage::TextInput { text }) => {
+                            let _ = text_tx.send(text).await;
+                        }
+                        Ok(protocol::InboundMessage::ActivityStart) => {
+                            tracing::debug!("Client signaled activity start");
+                        }
+                        Ok(protocol::InboundMessage::ActivityEnd) => {
+                            tracing::debug!("Client signaled activity end");
+                        }
+                        Ok(protocol::InboundMessage::Ping) => {
+                            // Activity was already recorded above; no reply needed here,
+                            // the server-driven Ping ticker handles keepalive.
+                        }
+                        Ok(protocol::InboundMessage::Setup { .. }) => {
+                            tracing::warn!("Ignoring duplicate setup message after session start");
+                        }
+                        Err(e) => {
+                            tracing::warn!("Dropping malformed client frame: {}", e);
+                            let _ = protocol_event_tx
+                                .send(ClientEvent::Json(e.to_outbound_json()))
+                                .await;
+                        }
+                    }
                 }
                 Ok(axum::extract::ws::Message::Close(_)) => {
                     tracing::info!("Client closed connection");
                     break;
                 }
+                Ok(axum::extract::ws::Message::Ping(_)) | Ok(axum::extract::ws::Message::Pong(_)) => {
+                    receive_activity.touch_frame().await;
+                }
                 Err(e) => {
                     tracing::error!("WebSocket receive error: {}", e);
                     break;
                 }
-                _ => {}
             }
         }
     });
 
     // Forward events from Gemini to client (use custom session duration)
     let session_timeout = Duration::from_secs(session_duration);
+    let ping_interval = Duration::from_secs(state.config.ping_interval_seconds);
+    let pong_timeout = Duration::from_secs(state.config.pong_timeout_seconds);
+    let inactivity_timeout = Duration::from_secs(state.config.inactivity_timeout_seconds);
 
     let forward_handle = tokio::spawn(async move {
         let deadline = tokio::time::Instant::now() + session_timeout;
+        let mut ping_ticker = tokio::time::interval(ping_interval);
+        ping_ticker.tick().await; // first tick fires immediately; consume it
 
         loop {
             tokio::select! {
+                _ = ping_ticker.tick() => {
+                    if ws_sender.send(axum::extract::ws::Message::Ping(Vec::new().into())).await.is_err() {
+                        break;
+                    }
+
+                    let frame_idle = activity.idle_since_frame().await;
+                    if frame_idle > pong_timeout {
+                        tracing::warn!("No Pong (or any frame) from client in {:?}, closing dead connection", frame_idle);
+                        let error_json = serde_json::json!({
+                            "error": { "message": "Connection appears dead (no Pong received)", "code": "PONG_TIMEOUT" }
+                        }).to_string();
+                        let _ = ws_sender.send(axum::extract::ws::Message::Text(error_json.into())).await;
+                        let _ = ws_sender.send(axum::extract::ws::Message::Close(None)).await;
+                        break;
+                    }
+
+                    let media_idle = activity.idle_since_media().await;
+                    if media_idle > inactivity_timeout {
+                        tracing::info!("No audio/text in {:?}, ending abandoned session", media_idle);
+                        let error_json = serde_json::json!({
+                            "error": { "message": "Session ended due to inactivity", "code": "INACTIVITY_TIMEOUT" }
+                        }).to_string();
+                        let _ = ws_sender.send(axum::extract::ws::Message::Text(error_json.into())).await;
+                        let _ = ws_sender.send(axum::extract::ws::Message::Close(None)).await;
+                        break;
+                    }
+                }
                 _ = tokio::time::sleep_until(deadline) => {
                     tracing::info!("Session time limit reached");
                     let _ = ws_sender
@@ -205,8 +462,24 @@ async fn handle_socket_inner(
                     break;
                 }
                 event = event_rx.recv() => {
+                    // Mirror every event to spectators before consuming it locally.
+                    if let Some(ref event) = event {
+                        let _ = spectator_tx.send(event.clone());
+                    }
+
                     match event {
-                        Some(ClientEvent::Audio(data)) => {
+                        Some(ClientEvent::Audio { data, timestamp_ms, duration_ms }) => {
+                            activity.touch_media().await;
+                            let ts_json = serde_json::json!({
+                                "audioTimestamp": { "startMs": timestamp_ms, "durationMs": duration_ms }
+                            }).to_string();
+                            if ws_sender
+                                .send(axum::extract::ws::Message::Text(ts_json.into()))
+                                .await
+                                .is_err()
+                            {
+                                break;
+                            }
                             if ws_sender
                                 .send(axum::extract::ws::Message::Binary(data.into()))
                                 .await
@@ -216,6 +489,7 @@ async fn handle_socket_inner(
                             }
                         }
                         Some(ClientEvent::Json(json)) => {
+                            activity.touch_media().await;
                             if ws_sender
                                 .send(axum::extract::ws::Message::Text(json.into()))
                                 .await
@@ -224,18 +498,23 @@ async fn handle_socket_inner(
                                 break;
                             }
                         }
-                        Some(ClientEvent::Error { message, stats }) => {
-                            // Send error as JSON message to client
-                            let error_json = serde_json::json!({
-                                "error": {
-                                    "message": message,
-                                    "code": "SESSION_ERROR",
-                                    "stats": stats
-                                }
-                            }).to_string();
-                            tracing::info!("Sending error to client: {}", error_json);
+                        Some(ClientEvent::Error { message, stats: _ }) => {
+                            let err = AppError::GeminiError(message);
+                            let event_msg = ClientEventMessage {
+                                server_content: None,
+                                tool_call: None,
+                                usage_metadata: None,
+                                session_stats: None,
+                                token_budget_warning: None,
+                                review_suggestion: None,
+                                error: Some(ClientErrorEvent::from(&err)),
+                            };
+                            let Ok(json) = serde_json::to_string(&event_msg) else {
+                                continue;
+                            };
+                            tracing::info!("Sending error to client: {}", json);
                             let _ = ws_sender
-                                .send(axum::extract::ws::Message::Text(error_json.into()))
+                                .send(axum::extract::ws::Message::Text(json.into()))
                                 .await;
                         }
                         Some(ClientEvent::SessionEnd { stats }) => {
@@ -250,6 +529,30 @@ async fn handle_socket_inner(
                                 .send(axum::extract::ws::Message::Text(end_json.into()))
                                 .await;
                         }
+                        Some(ClientEvent::ResumeHandle(handle)) => {
+                            let resume_json = serde_json::json!({
+                                "resumeHandle": { "handle": handle }
+                            }).to_string();
+                            let _ = ws_sender
+                                .send(axum::extract::ws::Message::Text(resume_json.into()))
+                                .await;
+                        }
+                        Some(ClientEvent::GoAway { time_left }) => {
+                            let go_away_json = serde_json::json!({
+                                "goAway": { "timeLeft": time_left }
+                            }).to_string();
+                            let _ = ws_sender
+                                .send(axum::extract::ws::Message::Text(go_away_json.into()))
+                                .await;
+                        }
+                        Some(ClientEvent::Reconnecting { attempt, max_attempts }) => {
+                            let reconnecting_json = serde_json::json!({
+                                "reconnecting": { "attempt": attempt, "maxAttempts": max_attempts }
+                            }).to_string();
+                            let _ = ws_sender
+                                .send(axum::extract::ws::Message::Text(reconnecting_json.into()))
+                                .await;
+                        }
                         Some(ClientEvent::Close) | None => {
                             let _ = ws_sender
                                 .send(axum::extract::ws::Message::Close(None))
@@ -269,6 +572,9 @@ async fn handle_socket_inner(
         _ = forward_handle => {}
     }
 
+    // Tear down every spectator now that the primary has disconnected.
+    state.unregister_share(&share_id).await;
+
     tracing::info!("WebSocket session ended");
     Ok(())
 }
@@ -285,16 +591,33 @@ pub struct SessionStats {
 }
 
 /// Events sent to the client.
-#[derive(Debug)]
+///
+/// `Clone` so a single event can be forwarded to both the primary client and
+/// any classroom spectators subscribed to the same share-id.
+#[derive(Debug, Clone)]
 pub enum ClientEvent {
-    /// Raw audio data
-    Audio(Vec<u8>),
+    /// Raw audio data, stamped with its start position and duration on the output
+    /// media timeline (milliseconds) so the client can align captions to the audio
+    /// it plays.
+    Audio {
+        data: Vec<u8>,
+        timestamp_ms: f64,
+        duration_ms: f64,
+    },
     /// JSON event (transcription, turn complete, etc.)
     Json(String),
     /// Error event with message and optional stats
     Error { message: String, stats: Option<SessionStats> },
     /// Session ended normally with stats
     SessionEnd { stats: SessionStats },
+    /// A fresh Gemini session-resumption handle is available; forwarded so the
+    /// client can pass it back as `resume_token` on a future `/api/auth` call.
+    ResumeHandle(String),
+    /// Gemini warned that it will close the connection soon (`goAway`).
+    GoAway { time_left: Option<String> },
+    /// The upstream backend is transparently reconnecting after a session-limit
+    /// close or `goAway`, so the UI can show continuity instead of an error.
+    Reconnecting { attempt: u32, max_attempts: u32 },
     /// Close connection
     Close,
 }
@@ -0,0 +1,184 @@
+//! Cursor-based history sync store, backed by SQLite.
+//!
+//! Shares `session_db_path` with `SessionStore` (a separate connection, its own
+//! table) rather than introducing another config knob. Records are opaque to the
+//! server - `data` is whatever JSON (plaintext or client-encrypted ciphertext) the
+//! caller pushed - this store only owns version comparison and cursor pagination.
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rusqlite::{params, Connection};
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// One record pushed by a client.
+pub struct HistoryRecord {
+    pub table_name: String,
+    pub row_id: String,
+    pub data: serde_json::Value,
+    pub version: i64,
+    pub deleted: bool,
+}
+
+/// Identifies which account's partition of `history_records` a push/pull call reads
+/// or writes. Not validated against any user/device directory here - the server only
+/// requires callers to present one, consistently, so one account's rows can never be
+/// read or overwritten by another's.
+pub type AccountId = str;
+
+/// One record read back out for a pull, stamped with the server-side timestamp a
+/// caller's next pull should resume after.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HistoryRecordOut {
+    pub table_name: String,
+    pub row_id: String,
+    pub data: serde_json::Value,
+    pub version: i64,
+    pub deleted: bool,
+    pub updated_at: i64,
+}
+
+/// SQLite-backed store for cross-device history sync via `/api/history`.
+///
+/// `rusqlite::Connection` is synchronous, so every query runs on a blocking-pool
+/// thread via `tokio::task::spawn_blocking` rather than on the async runtime.
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+impl HistoryStore {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS history_records (
+                account_id TEXT NOT NULL,
+                table_name TEXT NOT NULL,
+                row_id TEXT NOT NULL,
+                data TEXT NOT NULL,
+                version INTEGER NOT NULL,
+                deleted INTEGER NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL,
+                PRIMARY KEY (account_id, table_name, row_id)
+            );
+            CREATE INDEX IF NOT EXISTS idx_history_records_account_updated_at
+                ON history_records(account_id, updated_at);
+            ",
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// Run a blocking SQLite operation on the blocking thread pool.
+    async fn with_conn<T, F>(self: &Arc<Self>, f: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = this.conn.lock().expect("history store connection mutex poisoned");
+            f(&conn)
+        })
+        .await?
+        .map_err(Into::into)
+    }
+
+    /// Upsert a batch of pushed records into `account_id`'s partition. Last-writer-by-
+    /// version wins: a record is only applied if its `version` is greater than the
+    /// stored row's (or the row doesn't exist yet); a `deleted` tombstone always
+    /// overwrites a live row regardless of version, so a delete can never be "lost" to
+    /// a stale concurrent edit. Returns how many of the batch were actually applied.
+    pub async fn push(
+        self: &Arc<Self>,
+        account_id: &AccountId,
+        records: Vec<HistoryRecord>,
+    ) -> anyhow::Result<usize> {
+        let account_id = account_id.to_string();
+        self.with_conn(move |conn| {
+            let mut applied = 0;
+            for record in records {
+                let existing: Option<i64> = conn
+                    .query_row(
+                        "SELECT version FROM history_records
+                         WHERE account_id = ?1 AND table_name = ?2 AND row_id = ?3",
+                        params![account_id, record.table_name, record.row_id],
+                        |row| row.get(0),
+                    )
+                    .ok();
+
+                let should_apply = match existing {
+                    None => true,
+                    Some(current_version) => record.deleted || record.version > current_version,
+                };
+                if !should_apply {
+                    continue;
+                }
+
+                conn.execute(
+                    "INSERT INTO history_records
+                        (account_id, table_name, row_id, data, version, deleted, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                     ON CONFLICT(account_id, table_name, row_id) DO UPDATE SET
+                        data = excluded.data,
+                        version = excluded.version,
+                        deleted = excluded.deleted,
+                        updated_at = excluded.updated_at",
+                    params![
+                        account_id,
+                        record.table_name,
+                        record.row_id,
+                        record.data.to_string(),
+                        record.version,
+                        record.deleted as i64,
+                        now_ms(),
+                    ],
+                )?;
+                applied += 1;
+            }
+            Ok(applied)
+        })
+        .await
+    }
+
+    /// Every record (including tombstones) in `account_id`'s partition whose
+    /// `updated_at` is greater than `after`, oldest first, capped at `limit`.
+    pub async fn pull(
+        self: &Arc<Self>,
+        account_id: &AccountId,
+        after: i64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<HistoryRecordOut>> {
+        let account_id = account_id.to_string();
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT table_name, row_id, data, version, deleted, updated_at
+                 FROM history_records
+                 WHERE account_id = ?1 AND updated_at > ?2
+                 ORDER BY updated_at ASC LIMIT ?3",
+            )?;
+            stmt.query_map(params![account_id, after, limit as i64], |row| {
+                let data_text: String = row.get(2)?;
+                let data = serde_json::from_str(&data_text).unwrap_or(serde_json::Value::Null);
+                Ok(HistoryRecordOut {
+                    table_name: row.get(0)?,
+                    row_id: row.get(1)?,
+                    data,
+                    version: row.get(3)?,
+                    deleted: row.get::<_, i64>(4)? != 0,
+                    updated_at: row.get(5)?,
+                })
+            })?
+            .collect()
+        })
+        .await
+    }
+}
@@ -18,10 +18,17 @@
 //!                                        └──────────────────┘
 //! ```
 
+mod backend;
 mod config;
+mod diagnostics;
 mod error;
 mod gemini;
 mod handlers;
+mod history_store;
+mod metrics;
+mod protocol;
+mod session_store;
+mod session_token;
 mod state;
 
 use axum::{
@@ -57,13 +64,24 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Starting server with config: {:?}", config);
 
     // Create application state
-    let state = AppState::new(config.clone());
+    let state = AppState::new(config.clone())?;
 
     // Build router (backend-only, no static file serving)
     let app = Router::new()
         // API routes
         .route("/api/auth", post(handlers::auth::authenticate))
         .route("/api/health", get(handlers::health::health_check))
+        .route("/api/admin/diagnostics", get(handlers::diagnostics::recent_errors))
+        .route(
+            "/api/admin/session-tokens",
+            post(handlers::session_tokens::mint_session_token),
+        )
+        .route("/api/sessions/stats", get(handlers::sessions::token_usage))
+        .route("/api/sessions/{id}", get(handlers::sessions::replay_session))
+        .route(
+            "/api/history",
+            post(handlers::history::push_history).get(handlers::history::pull_history),
+        )
         // WebSocket endpoint
         .route("/ws", get(handlers::websocket::ws_handler))
         // Middleware
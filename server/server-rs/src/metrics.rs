@@ -0,0 +1,112 @@
+//! Optional Prometheus metrics for session telemetry, enabled via the `metrics` cargo
+//! feature. Without the feature, every function here is a no-op, so call sites in
+//! `gemini::client` don't need their own `#[cfg(feature = "metrics")]` guards.
+
+#[cfg(feature = "metrics")]
+mod enabled {
+    use once_cell::sync::Lazy;
+    use prometheus::{
+        register_int_counter, register_int_counter_vec, register_int_gauge, IntCounter,
+        IntCounterVec, IntGauge,
+    };
+
+    static ACTIVE_SESSIONS: Lazy<IntGauge> = Lazy::new(|| {
+        register_int_gauge!("gemini_active_sessions", "Number of live Gemini Live sessions").unwrap()
+    });
+
+    static AUDIO_BYTES_FORWARDED: Lazy<IntCounter> = Lazy::new(|| {
+        register_int_counter!(
+            "gemini_audio_bytes_forwarded_total",
+            "Total decoded audio bytes forwarded to clients"
+        )
+        .unwrap()
+    });
+
+    static TOKENS_CONSUMED: Lazy<IntCounterVec> = Lazy::new(|| {
+        register_int_counter_vec!(
+            "gemini_tokens_consumed_total",
+            "Tokens consumed per model",
+            &["model"]
+        )
+        .unwrap()
+    });
+
+    static RECONNECTS: Lazy<IntCounter> = Lazy::new(|| {
+        register_int_counter!(
+            "gemini_reconnects_total",
+            "Transparent reconnects after a session-limit close or goAway"
+        )
+        .unwrap()
+    });
+
+    static POLICY_CLOSES: Lazy<IntCounter> = Lazy::new(|| {
+        register_int_counter!(
+            "gemini_policy_closes_total",
+            "Sessions ended by an early Gemini policy violation close"
+        )
+        .unwrap()
+    });
+
+    pub fn session_started() {
+        ACTIVE_SESSIONS.inc();
+    }
+
+    pub fn session_ended() {
+        ACTIVE_SESSIONS.dec();
+    }
+
+    pub fn audio_bytes_forwarded(bytes: usize) {
+        AUDIO_BYTES_FORWARDED.inc_by(bytes as u64);
+    }
+
+    pub fn tokens_consumed(model: &str, total: u32) {
+        TOKENS_CONSUMED.with_label_values(&[model]).inc_by(total as u64);
+    }
+
+    pub fn reconnected() {
+        RECONNECTS.inc();
+    }
+
+    pub fn policy_closed() {
+        POLICY_CLOSES.inc();
+    }
+
+    /// Push the process's current metric snapshot to a Prometheus Pushgateway.
+    /// Errors are logged, not propagated - a down gateway shouldn't affect sessions.
+    pub async fn push(pushgateway_url: &str, job: &str) {
+        let url = pushgateway_url.to_string();
+        let job = job.to_string();
+        let result = tokio::task::spawn_blocking(move || {
+            prometheus::push_metrics(
+                &job,
+                prometheus::labels! {},
+                &url,
+                prometheus::gather(),
+                None,
+            )
+        })
+        .await;
+
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => tracing::warn!("Failed to push session metrics to Pushgateway: {}", e),
+            Err(e) => tracing::warn!("Metrics push task panicked: {}", e),
+        }
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+mod disabled {
+    pub fn session_started() {}
+    pub fn session_ended() {}
+    pub fn audio_bytes_forwarded(_bytes: usize) {}
+    pub fn tokens_consumed(_model: &str, _total: u32) {}
+    pub fn reconnected() {}
+    pub fn policy_closed() {}
+    pub async fn push(_pushgateway_url: &str, _job: &str) {}
+}
+
+#[cfg(feature = "metrics")]
+pub use enabled::*;
+#[cfg(not(feature = "metrics"))]
+pub use disabled::*;
@@ -0,0 +1,81 @@
+//! Typed client↔server WebSocket wire protocol.
+//!
+//! Previously, `handle_socket_inner` parsed untyped `serde_json::Value` and hand-walked
+//! nested keys (e.g. `realtimeInputConfig.automaticActivityDetection.speechConfig`) to
+//! sniff feature flags out of the client's setup payload. This module gives the `/ws`
+//! endpoint a small, serde-tagged contract for every message either side can send, so a
+//! malformed frame produces a structured error instead of being silently dropped or
+//! forwarded raw.
+
+use serde::{Deserialize, Serialize};
+
+use crate::handlers::websocket::SessionStats;
+
+/// Messages the client may send as a WebSocket text frame.
+///
+/// Raw PCM audio is still sent as a binary frame for efficiency; `AudioChunk` exists
+/// for callers that would rather send base64 JSON alongside their other messages.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum InboundMessage {
+    /// Initial session configuration, forwarded to the realtime backend verbatim.
+    Setup { setup: serde_json::Value },
+    /// Base64-encoded PCM audio chunk.
+    AudioChunk { data: String },
+    /// A free-form text turn.
+    TextInput { text: String },
+    /// Voice-activity-detection markers from a client doing its own VAD.
+    ActivityStart,
+    ActivityEnd,
+    /// Client-initiated keepalive, answered with `OutboundMessage::Pong`.
+    Ping,
+}
+
+/// Messages the server may send as a WebSocket text frame.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum OutboundMessage {
+    Transcript {
+        text: String,
+        finished: bool,
+        source: TranscriptSource,
+    },
+    Interrupt,
+    Error {
+        code: String,
+        message: String,
+    },
+    SessionEnd {
+        stats: SessionStats,
+    },
+    Pong,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TranscriptSource {
+    Input,
+    Output,
+}
+
+/// A malformed or unrecognized inbound frame.
+#[derive(Debug, thiserror::Error)]
+#[error("protocol error: {0}")]
+pub struct ProtocolError(pub String);
+
+impl ProtocolError {
+    /// Render this error as the JSON text frame the client should receive.
+    pub fn to_outbound_json(&self) -> String {
+        let outbound = OutboundMessage::Error {
+            code: "PROTOCOL_ERROR".to_string(),
+            message: self.0.clone(),
+        };
+        serde_json::to_string(&outbound)
+            .unwrap_or_else(|_| r#"{"type":"error","code":"PROTOCOL_ERROR","message":"unknown"}"#.to_string())
+    }
+}
+
+/// Parse a raw WebSocket text frame into a typed inbound message.
+pub fn parse_inbound(text: &str) -> Result<InboundMessage, ProtocolError> {
+    serde_json::from_str(text).map_err(|e| ProtocolError(format!("malformed message: {}", e)))
+}
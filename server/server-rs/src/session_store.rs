@@ -0,0 +1,334 @@
+//! Durable session/turn history, backed by SQLite.
+//!
+//! The receive loop in `gemini::client` already computes everything worth keeping -
+//! the transcript of each turn and the cumulative token counts at the point it
+//! finished - but previously only shipped it to the live client via `SessionStats`
+//! and then threw it away. This persists it to a normalized `sessions`/`turns`
+//! schema so a session's conversation can be replayed later and token spend can be
+//! tracked across every session the server has handled, not just the current one.
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rusqlite::{params, Connection};
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Cosine similarity between two equal-length embedding vectors, or `None` if they
+/// can't be compared (mismatched length, or either is the zero vector).
+fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() || a.is_empty() {
+        return None;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return None;
+    }
+    Some(dot / (norm_a * norm_b))
+}
+
+/// A completed turn awaiting persistence. One `ClientEventMessage` turn-complete
+/// produces up to two of these - one for the user's final input transcript, one for
+/// the model's final output transcript - sharing the same `session_id`/`turn_index`.
+pub struct TurnRecord {
+    pub session_id: String,
+    pub turn_index: u64,
+    pub role: &'static str,
+    pub text: String,
+    pub audio_chunk_count: u64,
+    pub prompt_tokens: u32,
+    pub response_tokens: u32,
+    pub total_tokens: u32,
+    pub interrupted: bool,
+}
+
+/// One prior learner utterance recalled from a nearest-neighbor query against the
+/// error-memory store, alongside how similar it was to the utterance being checked.
+pub struct ErrorMemoryMatch {
+    pub text: String,
+    pub similarity: f32,
+}
+
+/// A turn row read back out for replay.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TurnReplay {
+    pub turn_index: i64,
+    pub role: String,
+    pub text: String,
+    pub audio_chunk_count: i64,
+    pub prompt_tokens: i64,
+    pub response_tokens: i64,
+    pub total_tokens: i64,
+    pub interrupted: bool,
+    pub created_at: i64,
+}
+
+/// SQLite-backed store for session and per-turn history.
+///
+/// `rusqlite::Connection` is synchronous, so every query runs on a blocking-pool
+/// thread via `tokio::task::spawn_blocking` rather than on the async runtime.
+pub struct SessionStore {
+    conn: Mutex<Connection>,
+}
+
+impl SessionStore {
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "
+            CREATE TABLE IF NOT EXISTS sessions (
+                id TEXT PRIMARY KEY,
+                started_at INTEGER NOT NULL,
+                model TEXT NOT NULL,
+                language_pair TEXT
+            );
+
+            CREATE TABLE IF NOT EXISTS turns (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL REFERENCES sessions(id),
+                turn_index INTEGER NOT NULL,
+                role TEXT NOT NULL,
+                text TEXT NOT NULL,
+                audio_chunk_count INTEGER NOT NULL,
+                prompt_tokens INTEGER NOT NULL,
+                response_tokens INTEGER NOT NULL,
+                total_tokens INTEGER NOT NULL,
+                interrupted INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_turns_session
+                ON turns(session_id, turn_index);
+
+            CREATE TABLE IF NOT EXISTS vocabulary (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL REFERENCES sessions(id),
+                term TEXT NOT NULL,
+                context TEXT,
+                created_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_vocabulary_session
+                ON vocabulary(session_id);
+
+            CREATE TABLE IF NOT EXISTS error_memory (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL REFERENCES sessions(id),
+                turn_index INTEGER NOT NULL,
+                text TEXT NOT NULL,
+                embedding BLOB NOT NULL,
+                created_at INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_error_memory_created
+                ON error_memory(created_at);
+            CREATE INDEX IF NOT EXISTS idx_error_memory_session
+                ON error_memory(session_id);
+            ",
+        )?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    /// Run a blocking SQLite operation on the blocking thread pool.
+    async fn with_conn<T, F>(self: &Arc<Self>, f: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(&Connection) -> rusqlite::Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = this.conn.lock().expect("session store connection mutex poisoned");
+            f(&conn)
+        })
+        .await?
+        .map_err(Into::into)
+    }
+
+    /// Record a new session. A no-op (via `INSERT OR IGNORE`) if a reconnect already
+    /// recorded this `session_id`.
+    pub async fn start_session(
+        self: &Arc<Self>,
+        session_id: &str,
+        model: &str,
+        language_pair: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let session_id = session_id.to_string();
+        let model = model.to_string();
+        let language_pair = language_pair.map(str::to_string);
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO sessions (id, started_at, model, language_pair)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![session_id, now_ms(), model, language_pair],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Persist one completed turn.
+    pub async fn finalize_turn(self: &Arc<Self>, turn: TurnRecord) -> anyhow::Result<()> {
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO turns
+                 (session_id, turn_index, role, text, audio_chunk_count, prompt_tokens,
+                  response_tokens, total_tokens, interrupted, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    turn.session_id,
+                    turn.turn_index as i64,
+                    turn.role,
+                    turn.text,
+                    turn.audio_chunk_count as i64,
+                    turn.prompt_tokens as i64,
+                    turn.response_tokens as i64,
+                    turn.total_tokens as i64,
+                    turn.interrupted as i64,
+                    now_ms(),
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Replay every turn of a past session, in order, for a learner reviewing it.
+    pub async fn replay_session(self: &Arc<Self>, session_id: &str) -> anyhow::Result<Vec<TurnReplay>> {
+        let session_id = session_id.to_string();
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT turn_index, role, text, audio_chunk_count, prompt_tokens,
+                        response_tokens, total_tokens, interrupted, created_at
+                 FROM turns WHERE session_id = ?1 ORDER BY turn_index, role",
+            )?;
+            stmt.query_map(params![session_id], |row| {
+                Ok(TurnReplay {
+                    turn_index: row.get(0)?,
+                    role: row.get(1)?,
+                    text: row.get(2)?,
+                    audio_chunk_count: row.get(3)?,
+                    prompt_tokens: row.get(4)?,
+                    response_tokens: row.get(5)?,
+                    total_tokens: row.get(6)?,
+                    interrupted: row.get::<_, i64>(7)? != 0,
+                    created_at: row.get(8)?,
+                })
+            })?
+            .collect()
+        })
+        .await
+    }
+
+    /// Save a term to the learner's vocabulary deck, along with the context it came up
+    /// in (e.g. the sentence it was used in), via the `save_vocabulary` tool.
+    pub async fn save_vocabulary(
+        self: &Arc<Self>,
+        session_id: &str,
+        term: &str,
+        context: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let session_id = session_id.to_string();
+        let term = term.to_string();
+        let context = context.map(str::to_string);
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO vocabulary (session_id, term, context, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![session_id, term, context, now_ms()],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Save one learner utterance's embedding for later recall, keyed by the turn it
+    /// came from. Returns the new row's id, so callers can exclude it (as the just-saved
+    /// self-match) from a subsequent `find_similar_errors` query.
+    pub async fn save_error_embedding(
+        self: &Arc<Self>,
+        session_id: &str,
+        turn_index: u64,
+        text: &str,
+        embedding: &[f32],
+    ) -> anyhow::Result<i64> {
+        let session_id = session_id.to_string();
+        let text = text.to_string();
+        let embedding_bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
+        self.with_conn(move |conn| {
+            conn.execute(
+                "INSERT INTO error_memory (session_id, turn_index, text, embedding, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![session_id, turn_index as i64, text, embedding_bytes, now_ms()],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+        .await
+    }
+
+    /// Find prior utterances from this same `session_id` whose embedding is most
+    /// similar to `embedding`, most-similar first, excluding `exclude_id` (the row
+    /// just saved by the caller via `save_error_embedding`, which would otherwise
+    /// self-match at similarity 1.0 and inflate its own recurring-mistake count).
+    /// Scoped to one session rather than the whole error_memory table - this server
+    /// has no stable, persistent learner identity to match on across sessions (the
+    /// Gemini `session_id` is a fresh per-connection value), so matching beyond this
+    /// session's own rows would surface one learner's transcribed speech as another's
+    /// "recurring mistake". Brute-force cosine similarity over the session's stored
+    /// embeddings - fine at this scale, but the first thing to replace with a real
+    /// vector index if this ever needs to scale further.
+    pub async fn find_similar_errors(
+        self: &Arc<Self>,
+        session_id: &str,
+        embedding: Vec<f32>,
+        limit: usize,
+        exclude_id: i64,
+    ) -> anyhow::Result<Vec<ErrorMemoryMatch>> {
+        let session_id = session_id.to_string();
+        self.with_conn(move |conn| {
+            let mut stmt =
+                conn.prepare("SELECT text, embedding FROM error_memory WHERE session_id = ?1 AND id != ?2")?;
+            let mut matches: Vec<ErrorMemoryMatch> = stmt
+                .query_map(params![session_id, exclude_id], |row| {
+                    let text: String = row.get(0)?;
+                    let bytes: Vec<u8> = row.get(1)?;
+                    Ok((text, bytes))
+                })?
+                .filter_map(|r| r.ok())
+                .filter_map(|(text, bytes)| {
+                    let candidate: Vec<f32> = bytes
+                        .chunks_exact(4)
+                        .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                        .collect();
+                    cosine_similarity(&embedding, &candidate).map(|similarity| ErrorMemoryMatch { text, similarity })
+                })
+                .collect();
+            matches.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap_or(std::cmp::Ordering::Equal));
+            matches.truncate(limit);
+            Ok(matches)
+        })
+        .await
+    }
+
+    /// Total tokens spent across every recorded session, for usage tracking over time.
+    /// `total_tokens` is a per-session cumulative reading, so this sums the last
+    /// (highest) reading seen per session rather than summing every turn row.
+    pub async fn total_tokens_spent(self: &Arc<Self>) -> anyhow::Result<i64> {
+        self.with_conn(|conn| {
+            conn.query_row(
+                "SELECT COALESCE(SUM(max_total), 0) FROM (
+                     SELECT MAX(total_tokens) AS max_total FROM turns GROUP BY session_id
+                 )",
+                [],
+                |row| row.get(0),
+            )
+        })
+        .await
+    }
+}
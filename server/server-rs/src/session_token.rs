@@ -0,0 +1,221 @@
+//! Stateless, cryptographically signed scoping tokens for `/ws` connections.
+//!
+//! The existing `/api/auth` flow (see `state.rs`) issues a random single-use token
+//! this server tracks in memory until it's consumed - fine for attaching a browser to
+//! a particular share/resume flow, but it gives a trusted "front door" no way to
+//! constrain *what* a connecting client can ask Gemini for. `SessionTokenIssuer` signs
+//! a compact `{ session_id, model, voice_name, exp }` payload with Ed25519 so a minter
+//! holding the private key can hand out a token scoped to exactly one model/voice, and
+//! this server can verify it on `/ws` upgrade without remembering anything about it.
+//! The minter is `POST /api/admin/session-tokens` (see `handlers::session_tokens`),
+//! gated by the same `ADMIN_API_KEY` as the rest of `/api/admin/*`.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand_core::OsRng;
+use secrecy::{ExposeSecret, SecretBox};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+pub(crate) fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// What a signed session token authorizes: one session id, pinned to one model and
+/// voice, valid until `exp` (Unix seconds).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionTokenClaims {
+    pub session_id: String,
+    pub model: String,
+    pub voice_name: String,
+    pub exp: i64,
+}
+
+impl SessionTokenClaims {
+    fn is_expired(&self) -> bool {
+        self.exp < now_secs()
+    }
+}
+
+/// Mints and verifies signed session tokens with one Ed25519 keypair.
+///
+/// The private key lives behind `secrecy::SecretBox` so an accidental `{:?}` of
+/// `AppState` (or anything holding this) can't leak it.
+pub struct SessionTokenIssuer {
+    signing_key: SecretBox<SigningKey>,
+    verifying_key: VerifyingKey,
+}
+
+impl SessionTokenIssuer {
+    /// Generate a fresh keypair, valid for this process's lifetime. Tokens minted
+    /// before a restart stop verifying - acceptable since these are meant to be
+    /// short-lived anyway, but only suitable for a single-instance deployment with
+    /// no `SESSION_TOKEN_SIGNING_KEY` configured; prefer `from_seed` otherwise.
+    pub fn generate() -> Self {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let verifying_key = signing_key.verifying_key();
+        Self {
+            signing_key: SecretBox::new(Box::new(signing_key)),
+            verifying_key,
+        }
+    }
+
+    /// Load a keypair from a persisted 32-byte seed (base64), so the signing key
+    /// survives restarts and can be shared across server instances - required for
+    /// an external minter (e.g. `POST /api/admin/session-tokens` on a different
+    /// instance) to issue tokens this instance will accept.
+    pub fn from_seed(seed_b64: &str) -> anyhow::Result<Self> {
+        let seed_bytes = BASE64.decode(seed_b64)?;
+        let seed: [u8; 32] = seed_bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("SESSION_TOKEN_SIGNING_KEY must decode to exactly 32 bytes"))?;
+        let signing_key = SigningKey::from_bytes(&seed);
+        let verifying_key = signing_key.verifying_key();
+        Ok(Self {
+            signing_key: SecretBox::new(Box::new(signing_key)),
+            verifying_key,
+        })
+    }
+
+    /// Sign a new token for `claims`, as `base64(payload).base64(signature)`.
+    pub fn mint(&self, claims: &SessionTokenClaims) -> anyhow::Result<String> {
+        let payload = serde_json::to_vec(claims)?;
+        let signature = self.signing_key.expose_secret().sign(&payload);
+        Ok(format!("{}.{}", BASE64.encode(&payload), BASE64.encode(signature.to_bytes())))
+    }
+
+    /// Sign an arbitrary opaque string with this process's key, as a base64 MAC.
+    /// Used to hand a client a credential proving it holds a particular value (e.g.
+    /// a session id) without this server having to remember anything about it -
+    /// the same "server holds the key, caller holds an unforgeable proof" shape as
+    /// `mint`/`verify`, just without the `SessionTokenClaims` envelope.
+    pub fn sign_opaque(&self, data: &str) -> String {
+        let signature = self.signing_key.expose_secret().sign(data.as_bytes());
+        BASE64.encode(signature.to_bytes())
+    }
+
+    /// Verify a credential previously produced by `sign_opaque` for `data`.
+    pub fn verify_opaque(&self, data: &str, signature_b64: &str) -> bool {
+        let Ok(signature_bytes) = BASE64.decode(signature_b64) else {
+            return false;
+        };
+        let Ok(signature_bytes): Result<[u8; 64], _> = signature_bytes.as_slice().try_into() else {
+            return false;
+        };
+        let signature = Signature::from_bytes(&signature_bytes);
+        self.verifying_key.verify(data.as_bytes(), &signature).is_ok()
+    }
+
+    /// Verify a token's signature and expiry, returning its claims if both hold.
+    /// A malformed or forged token maps to `InvalidToken`; a well-formed but expired
+    /// one maps to `SessionExpired`, so callers can tell the two apart.
+    pub fn verify(&self, token: &str) -> Result<SessionTokenClaims, AppError> {
+        let (payload_b64, signature_b64) = token.split_once('.').ok_or(AppError::InvalidToken)?;
+
+        let payload = BASE64.decode(payload_b64).map_err(|_| AppError::InvalidToken)?;
+        let signature_bytes = BASE64.decode(signature_b64).map_err(|_| AppError::InvalidToken)?;
+        let signature_bytes: [u8; 64] = signature_bytes.as_slice().try_into().map_err(|_| AppError::InvalidToken)?;
+        let signature = Signature::from_bytes(&signature_bytes);
+
+        self.verifying_key.verify(&payload, &signature).map_err(|_| AppError::InvalidToken)?;
+
+        let claims: SessionTokenClaims = serde_json::from_slice(&payload).map_err(|_| AppError::InvalidToken)?;
+
+        if claims.is_expired() {
+            return Err(AppError::SessionExpired);
+        }
+
+        Ok(claims)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(session_id: &str, exp_offset_secs: i64) -> SessionTokenClaims {
+        SessionTokenClaims {
+            session_id: session_id.to_string(),
+            model: "gemini-2.0-flash-live-001".to_string(),
+            voice_name: "Aoede".to_string(),
+            exp: now_secs() + exp_offset_secs,
+        }
+    }
+
+    #[test]
+    fn mint_then_verify_round_trips_claims() {
+        let issuer = SessionTokenIssuer::generate();
+        let token = issuer.mint(&claims("session-1", 60)).unwrap();
+
+        let verified = issuer.verify(&token).unwrap();
+        assert_eq!(verified.session_id, "session-1");
+        assert_eq!(verified.model, "gemini-2.0-flash-live-001");
+        assert_eq!(verified.voice_name, "Aoede");
+    }
+
+    #[test]
+    fn verify_rejects_expired_token() {
+        let issuer = SessionTokenIssuer::generate();
+        let token = issuer.mint(&claims("session-1", -1)).unwrap();
+
+        assert!(matches!(issuer.verify(&token), Err(AppError::SessionExpired)));
+    }
+
+    #[test]
+    fn verify_rejects_token_signed_by_a_different_key() {
+        let minter = SessionTokenIssuer::generate();
+        let verifier = SessionTokenIssuer::generate();
+        let token = minter.mint(&claims("session-1", 60)).unwrap();
+
+        assert!(matches!(verifier.verify(&token), Err(AppError::InvalidToken)));
+    }
+
+    #[test]
+    fn verify_rejects_tampered_payload() {
+        let issuer = SessionTokenIssuer::generate();
+        let token = issuer.mint(&claims("session-1", 60)).unwrap();
+        let (payload_b64, signature_b64) = token.split_once('.').unwrap();
+
+        let mut tampered_claims = claims("session-2", 60);
+        tampered_claims.exp = claims("session-1", 60).exp;
+        let tampered_payload = BASE64.encode(serde_json::to_vec(&tampered_claims).unwrap());
+        let tampered_token = format!("{}.{}", tampered_payload, signature_b64);
+        // Sanity: the swap actually produced a different payload than the original.
+        assert_ne!(tampered_payload, payload_b64);
+
+        assert!(matches!(issuer.verify(&tampered_token), Err(AppError::InvalidToken)));
+    }
+
+    #[test]
+    fn verify_rejects_malformed_token() {
+        let issuer = SessionTokenIssuer::generate();
+        assert!(matches!(issuer.verify("not-a-valid-token"), Err(AppError::InvalidToken)));
+    }
+
+    #[test]
+    fn sign_opaque_then_verify_opaque_round_trips() {
+        let issuer = SessionTokenIssuer::generate();
+        let signature = issuer.sign_opaque("share-id-123");
+
+        assert!(issuer.verify_opaque("share-id-123", &signature));
+        assert!(!issuer.verify_opaque("share-id-456", &signature));
+    }
+
+    #[test]
+    fn from_seed_is_deterministic_and_mutually_verifiable() {
+        let seed = BASE64.encode([7u8; 32]);
+        let issuer_a = SessionTokenIssuer::from_seed(&seed).unwrap();
+        let issuer_b = SessionTokenIssuer::from_seed(&seed).unwrap();
+
+        let token = issuer_a.mint(&claims("session-1", 60)).unwrap();
+        let verified = issuer_b.verify(&token).unwrap();
+        assert_eq!(verified.session_id, "session-1");
+    }
+}
@@ -7,26 +7,53 @@ use std::{
     sync::Arc,
     time::{Duration, Instant},
 };
-use tokio::sync::RwLock;
+use axum::http::{header::AUTHORIZATION, HeaderMap};
+use subtle::ConstantTimeEq;
+use tokio::sync::{broadcast, RwLock};
 
-use crate::config::Config;
+use crate::{
+    config::Config,
+    error::{AppError, Result},
+    handlers::websocket::ClientEvent,
+    history_store::HistoryStore,
+    session_store::SessionStore,
+    session_token::SessionTokenIssuer,
+};
 
 /// Token expiry duration (30 seconds, matching Python implementation).
 const TOKEN_EXPIRY_SECONDS: u64 = 30;
 
+/// Capacity of the per-session spectator broadcast channel.
+const SPECTATOR_CHANNEL_CAPACITY: usize = 256;
+
 /// Session token with creation timestamp and custom duration.
 #[derive(Debug, Clone)]
 pub struct SessionToken {
     created_at: Instant,
     /// Custom session duration in seconds
-    pub duration: u64,
+    duration: u64,
+    /// Share-id of an existing session this token should join as a read-only spectator,
+    /// rather than driving its own upstream Gemini connection.
+    join: Option<String>,
+    /// Share-id assigned to this token if it ends up driving the session (the default).
+    share_id: Option<String>,
+    /// Gemini session-resumption handle to replay into the upstream setup message,
+    /// carried over from a previous WebSocket connection via `resume_token`.
+    resume_handle: Option<String>,
 }
 
 impl SessionToken {
-    pub fn new(duration: u64) -> Self {
+    pub fn new(duration: u64, join: Option<String>, resume_handle: Option<String>) -> Self {
         Self {
             created_at: Instant::now(),
             duration,
+            share_id: if join.is_none() {
+                Some(uuid::Uuid::new_v4().to_string())
+            } else {
+                None
+            },
+            join,
+            resume_handle,
         }
     }
 
@@ -35,53 +62,168 @@ impl SessionToken {
     }
 }
 
+/// A consumed token's session parameters, returned once and then discarded.
+#[derive(Debug, Clone)]
+pub struct ConsumedToken {
+    pub duration: u64,
+    pub join: Option<String>,
+    pub share_id: Option<String>,
+    pub resume_handle: Option<String>,
+}
+
 /// Shared application state.
 #[derive(Clone)]
 pub struct AppState {
     pub config: Config,
+    /// Durable session/turn history, so a conversation survives past the live WebSocket.
+    pub session_store: Arc<SessionStore>,
+    /// Cross-device history sync store backing `/api/history`, shared with
+    /// `session_store`'s database file.
+    pub history_store: Arc<HistoryStore>,
     /// Valid session tokens (token string -> SessionToken)
     tokens: Arc<RwLock<HashMap<String, SessionToken>>>,
+    /// Active "classroom" sessions, keyed by share-id, used to fan audio/transcript
+    /// events out to read-only spectators of a single primary Gemini connection.
+    live_sessions: Arc<RwLock<HashMap<String, broadcast::Sender<ClientEvent>>>>,
+    /// Latest Gemini session-resumption handle seen for a given (now-consumed)
+    /// session token, so a later `/api/auth` call with `resume_token` can replay it.
+    resume_handles: Arc<RwLock<HashMap<String, String>>>,
+    /// Mints and verifies signed `scope_token`s that pin a `/ws` connection to one
+    /// model/voice without this server having to remember anything about them.
+    pub session_tokens: Arc<SessionTokenIssuer>,
 }
 
 impl AppState {
-    pub fn new(config: Config) -> Self {
-        Self {
+    pub fn new(config: Config) -> anyhow::Result<Self> {
+        let session_store = Arc::new(SessionStore::open(std::path::Path::new(&config.session_db_path))?);
+        let history_store = Arc::new(HistoryStore::open(std::path::Path::new(&config.session_db_path))?);
+
+        let session_tokens = match config.session_token_signing_key.as_deref() {
+            Some(seed) => SessionTokenIssuer::from_seed(seed)?,
+            None => {
+                tracing::warn!(
+                    "SESSION_TOKEN_SIGNING_KEY not set; using an ephemeral key for this process. \
+                     Scope tokens minted via /api/admin/session-tokens won't verify after a restart \
+                     or on another instance."
+                );
+                SessionTokenIssuer::generate()
+            }
+        };
+
+        Ok(Self {
+            session_store,
+            history_store,
             config,
             tokens: Arc::new(RwLock::new(HashMap::new())),
-        }
+            live_sessions: Arc::new(RwLock::new(HashMap::new())),
+            resume_handles: Arc::new(RwLock::new(HashMap::new())),
+            session_tokens: Arc::new(session_tokens),
+        })
     }
 
     /// Create a new session token with a custom duration.
     ///
-    /// Returns the token string that the client should use to connect to the WebSocket.
-    pub async fn create_token(&self, duration: u64) -> String {
+    /// If `resume_token` names a previous session with a stored resumption handle,
+    /// that handle is attached so the next `/ws` connection can replay it into the
+    /// Gemini setup message and continue the same conversation.
+    ///
+    /// Returns the token string the client should use to connect to the WebSocket,
+    /// along with the share-id assigned to it if it will be the session's primary driver.
+    pub async fn create_token(
+        &self,
+        duration: u64,
+        join: Option<String>,
+        resume_token: Option<String>,
+    ) -> (String, Option<String>) {
+        let resume_handle = match resume_token {
+            Some(ref prev) => self.resume_handles.read().await.get(prev).cloned(),
+            None => None,
+        };
+
         let token = uuid::Uuid::new_v4().to_string();
         let mut tokens = self.tokens.write().await;
 
         // Cleanup expired tokens
         tokens.retain(|_, t| !t.is_expired());
 
-        // Insert new token with custom duration
-        tokens.insert(token.clone(), SessionToken::new(duration));
+        let session_token = SessionToken::new(duration, join, resume_handle);
+        let share_id = session_token.share_id.clone();
+        tokens.insert(token.clone(), session_token);
 
-        token
+        (token, share_id)
     }
 
     /// Validate and consume a token (one-time use).
     ///
-    /// Returns `Some(duration)` if the token was valid and has been consumed,
-    /// where duration is the session time limit in seconds.
-    pub async fn consume_token(&self, token: &str) -> Option<u64> {
+    /// Returns `Some(ConsumedToken)` if the token was valid and has been consumed.
+    pub async fn consume_token(&self, token: &str) -> Option<ConsumedToken> {
         let mut tokens = self.tokens.write().await;
 
-        if let Some(session_token) = tokens.remove(token) {
-            if !session_token.is_expired() {
-                Some(session_token.duration)
-            } else {
-                None
-            }
-        } else {
-            None
+        let session_token = tokens.remove(token)?;
+        if session_token.is_expired() {
+            return None;
+        }
+
+        Some(ConsumedToken {
+            duration: session_token.duration,
+            join: session_token.join,
+            share_id: session_token.share_id,
+            resume_handle: session_token.resume_handle,
+        })
+    }
+
+    /// Store the latest Gemini session-resumption handle, keyed by the session token
+    /// that was used to open the WebSocket connection it came from.
+    pub async fn store_resume_handle(&self, session_token: &str, handle: String) {
+        self.resume_handles.write().await.insert(session_token.to_string(), handle);
+    }
+
+    /// Drop a stored resumption handle, e.g. once Gemini reports it's no longer resumable.
+    pub async fn invalidate_resume_handle(&self, session_token: &str) {
+        self.resume_handles.write().await.remove(session_token);
+    }
+
+    /// Register a share-id so spectators can subscribe to its broadcast stream.
+    pub async fn register_share(&self, share_id: String, tx: broadcast::Sender<ClientEvent>) {
+        self.live_sessions.write().await.insert(share_id, tx);
+    }
+
+    /// Tear down a share-id. Dropping the stored sender closes every subscriber's
+    /// receiver, which is how spectators learn that the primary disconnected.
+    pub async fn unregister_share(&self, share_id: &str) {
+        self.live_sessions.write().await.remove(share_id);
+    }
+
+    /// Subscribe to an existing share-id's broadcast stream, if it's live.
+    pub async fn subscribe_share(&self, share_id: &str) -> Option<broadcast::Receiver<ClientEvent>> {
+        self.live_sessions.read().await.get(share_id).map(|tx| tx.subscribe())
+    }
+
+    /// Spectator channel capacity, exposed so callers don't need to duplicate the constant.
+    pub fn spectator_channel_capacity() -> usize {
+        SPECTATOR_CHANNEL_CAPACITY
+    }
+
+    /// Check an `Authorization: Bearer <key>` header against `config.admin_api_key`,
+    /// for `/api/admin/*` routes. Fails closed: if no admin key is configured, every
+    /// request is rejected rather than left open. Compared in constant time so a
+    /// timing difference between bytes can't be used to recover the key one byte
+    /// at a time.
+    pub fn check_admin_key(&self, headers: &HeaderMap) -> Result<()> {
+        let configured = self
+            .config
+            .admin_api_key
+            .as_deref()
+            .ok_or_else(|| AppError::ConfigError("ADMIN_API_KEY is not configured".to_string()))?;
+
+        let presented = headers
+            .get(AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+
+        match presented {
+            Some(key) if key.as_bytes().ct_eq(configured.as_bytes()).into() => Ok(()),
+            _ => Err(AppError::AuthError("Invalid or missing admin API key".to_string())),
         }
     }
 }